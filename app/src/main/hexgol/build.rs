@@ -1,7 +1,18 @@
 // use std::env;
 // use std::path::PathBuf;
 
+/// Pixels per glyph in `font.txt`.
+const GLYPH_SIZE: [usize; 2] = [5, 7];
+const ATLAS_COLUMNS: usize = 16;
+
 fn main() {
+    bake_font();
+
+    // Desktop builds leave out the app, so there's no GameActivity to link.
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("android") {
+        return;
+    }
+
     // // Tell cargo to invalidate the built crate whenever the wrapper changes
     // println!("cargo:rerun-if-changed=wrapper.h");
 
@@ -53,3 +64,79 @@ fn main() {
     println!("cargo:rustc-link-lib=c++abi");
     println!("cargo:rustc-link-lib=android");
 }
+
+/// Turns `font.txt` into a single-channel atlas, written to `$OUT_DIR/font.rs`
+/// along with its layout.
+fn bake_font() {
+    println!("cargo:rerun-if-changed=font.txt");
+
+    let text = std::fs::read_to_string("font.txt").expect("Couldn't read font.txt");
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"));
+
+    let mut glyphs: Vec<(u8, Vec<&str>)> = vec![];
+    while let Some(code) = lines.next() {
+        let code = u8::from_str_radix(code, 16).expect("Bad character code in font.txt");
+        let rows: Vec<&str> = lines.by_ref().take(GLYPH_SIZE[1]).collect();
+        assert!(
+            rows.len() == GLYPH_SIZE[1] && rows.iter().all(|row| row.len() == GLYPH_SIZE[0]),
+            "Glyph {:02x} in font.txt isn't {}x{}",
+            code,
+            GLYPH_SIZE[0],
+            GLYPH_SIZE[1]
+        );
+        glyphs.push((code, rows));
+    }
+    glyphs.sort_by_key(|(code, _rows)| *code);
+    let first = glyphs[0].0;
+    for (i, (code, _rows)) in glyphs.iter().enumerate() {
+        assert_eq!(
+            *code as usize,
+            first as usize + i,
+            "font.txt skips characters"
+        );
+    }
+
+    // A blank pixel right and below each glyph keeps neighbours from bleeding in.
+    let cell = [GLYPH_SIZE[0] + 1, GLYPH_SIZE[1] + 1];
+    let size = [
+        ATLAS_COLUMNS * cell[0],
+        glyphs.len().div_ceil(ATLAS_COLUMNS) * cell[1],
+    ];
+    let mut atlas = vec![0u8; size[0] * size[1]];
+    for (i, (_code, rows)) in glyphs.iter().enumerate() {
+        let corner = [(i % ATLAS_COLUMNS) * cell[0], (i / ATLAS_COLUMNS) * cell[1]];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, pixel) in row.bytes().enumerate() {
+                if pixel == b'#' {
+                    atlas[(corner[1] + y) * size[0] + corner[0] + x] = 255;
+                }
+            }
+        }
+    }
+
+    let source = format!(
+        "pub const FIRST_CHAR: u8 = {};\n\
+         pub const GLYPH_COUNT: usize = {};\n\
+         pub const GLYPH_SIZE: [u32; 2] = [{}, {}];\n\
+         pub const CELL_SIZE: [u32; 2] = [{}, {}];\n\
+         pub const ATLAS_COLUMNS: u32 = {};\n\
+         pub const ATLAS_SIZE: [u32; 2] = [{}, {}];\n\
+         pub static ATLAS: [u8; {}] = {:?};\n",
+        first,
+        glyphs.len(),
+        GLYPH_SIZE[0],
+        GLYPH_SIZE[1],
+        cell[0],
+        cell[1],
+        ATLAS_COLUMNS,
+        size[0],
+        size[1],
+        atlas.len(),
+        atlas,
+    );
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("font.rs"), source).expect("Couldn't write font.rs");
+}
@@ -394,6 +394,14 @@ static void onNativeWindowDestroyed(GameActivity* activity,
   android_app_set_window((struct android_app*)activity->instance, NULL);
 }
 
+// Input isn't posted to the looper, so wake it in case the main loop is
+// blocked waiting for something to draw.
+static void android_app_wake(struct android_app* android_app) {
+  if (android_app->looper != NULL) {
+    ALooper_wake(android_app->looper);
+  }
+}
+
 static void onTouchEvent(GameActivity* activity,
                          const GameActivityMotionEvent* event) {
   struct android_app* android_app = (struct android_app*)activity->instance;
@@ -406,6 +414,7 @@ static void onTouchEvent(GameActivity* activity,
     ++android_app->motionEventsCount;
   }
   pthread_mutex_unlock(&android_app->mutex);
+  android_app_wake(android_app);
 }
 
 void android_app_clear_motion_events(struct android_app* android_app) {
@@ -430,6 +439,7 @@ static void onKeyDown(GameActivity* activity, const GameActivityKeyEvent* event)
   }
 
   pthread_mutex_unlock(&android_app->mutex);
+  android_app_wake(android_app);
 }
 
 void android_app_clear_key_down_events(struct android_app* android_app) {
@@ -454,6 +464,7 @@ static void onKeyUp(GameActivity* activity, const GameActivityKeyEvent* event) {
   }
 
   pthread_mutex_unlock(&android_app->mutex);
+  android_app_wake(android_app);
 }
 
 void android_app_clear_key_up_events(struct android_app* android_app) {
@@ -473,6 +484,7 @@ static void onTextInputEvent(GameActivity* activity,
 
   android_app->textInputState = 1;
   pthread_mutex_unlock(&android_app->mutex);
+  android_app_wake(android_app);
 }
 
 JNIEXPORT
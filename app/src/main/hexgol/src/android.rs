@@ -0,0 +1,1676 @@
+//! The app itself: GameActivity's entry points, input and the main loop.
+
+use crate::export::*;
+use crate::ffi::*;
+use crate::game::*;
+use crate::hud::*;
+use crate::input::*;
+use crate::panel::*;
+use crate::picker::*;
+use crate::renderer::*;
+use crate::settings::*;
+use crate::theme::*;
+use crate::{assets, java, keyboard};
+
+use std::collections::HashSet;
+use std::ffi::c_void;
+use std::path::PathBuf;
+use std::ptr::addr_of_mut;
+use std::time::{Duration, Instant};
+
+struct Renderer {
+    board: Board,
+    grid: Grid,
+    /// `None` where the device can't simulate on the GPU.
+    life: Option<GpuLife>,
+    preview_instanced: InstancedMesh,
+    preview_outline: InstancedMesh,
+    graph: Graph,
+    heatmap: Heatmap,
+    overlay: Overlay,
+    text: Text,
+    /// The settings panel, drawn above everything else.
+    panel: Overlay,
+    panel_text: Text,
+    gfx: Graphics,
+    /// Generation last uploaded to the board, to tell steps from edits.
+    generation: u64,
+}
+impl Renderer {
+    fn new(mut gfx: Graphics, board_size: i32, theme: &Theme) -> Self {
+        gfx.set_clear_color(theme.background);
+        let board = Board::new(&gfx, board_size, theme.live, theme.dying);
+        let life = GpuLife::new(&gfx, &board);
+        let grid = Grid::new(&gfx, board_size, theme.grid, theme.border);
+        let hex = MeshBuilder::new_hexagon([0.0, 0.0], 1.0).build(gfx.context());
+        let preview_instanced = InstancedMesh::new(hex, gfx.context(), &[]);
+        let outline = MeshBuilder::new_hexagon_outline([0.0, 0.0], 1.0).build(gfx.context());
+        let preview_outline = InstancedMesh::new(outline, gfx.context(), &[]);
+        let graph = Graph::new(&gfx);
+        let heatmap = Heatmap::new(&gfx);
+        let overlay = Overlay::new(&gfx);
+        let text = Text::new(&gfx);
+        let panel = Overlay::new(&gfx);
+        let panel_text = Text::new(&gfx);
+
+        Self {
+            gfx,
+            board,
+            grid,
+            life,
+            preview_instanced,
+            preview_outline,
+            graph,
+            heatmap,
+            overlay,
+            text,
+            panel,
+            panel_text,
+            generation: 0,
+        }
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.gfx.set_clear_color(theme.background);
+        self.board
+            .set_colors(self.gfx.context(), theme.live, theme.dying);
+        self.grid = Grid::new(&self.gfx, self.board.size(), theme.grid, theme.border);
+    }
+}
+
+/// A pattern picked from the library or pasted, waiting to be placed.
+struct Stamp {
+    pattern: Pattern,
+    /// Index into the library, if it came from there.
+    picked: Option<usize>,
+    /// Where the preview sits. Tapping it again places the pattern.
+    anchor: Option<HexInt>,
+    /// Pinch rotation not yet turned into a 60° step.
+    twist: f32,
+}
+
+/// Shape dragged out while selecting.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum SelectShape {
+    Rect,
+    Hex,
+}
+
+/// How the board is shown.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum View {
+    Cells,
+    /// How often cells were alive lately.
+    Heatmap,
+    /// Cells with fading traces where they have been.
+    Trails,
+}
+impl View {
+    const ALL: [Self; 3] = [Self::Cells, Self::Heatmap, Self::Trails];
+}
+
+/// Something to hand to other apps through the share sheet.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Share {
+    /// A picture of the board.
+    Image,
+    /// The selection, or the whole board, as pattern text.
+    Pattern,
+}
+
+/// What to do once the universe settles into a cycle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum OnCycle {
+    Continue,
+    Pause,
+    Reseed,
+}
+impl OnCycle {
+    const ALL: [Self; 3] = [Self::Continue, Self::Pause, Self::Reseed];
+}
+
+struct App {
+    game: HexGOL,
+    camera: Camera,
+    gestures: Gestures,
+    renderer: Option<Renderer>,
+    /// The app's internal storage directory.
+    data_dir: Option<PathBuf>,
+    resumed: bool,
+    /// Whether the simulation is ticking.
+    running: bool,
+    on_cycle: OnCycle,
+    history: History,
+    stats: Stats,
+    /// Recent generations for the heatmap and trails. Generations run on the
+    /// GPU aren't recorded.
+    activity: Activity,
+    view: View,
+    show_graph: bool,
+    show_grid: bool,
+    show_hud: bool,
+    /// Window edges the HUD keeps clear of.
+    insets: Insets,
+    frames: FrameCounter,
+    settings: Settings,
+    /// Graphics API chosen at startup. Changes wait for a restart.
+    backend: GraphicsBackend,
+    /// The built-in themes, and the one from the theme config if there is one.
+    themes: Vec<Theme>,
+    theme: Theme,
+    /// Whether births and deaths are animated between generations.
+    animate: bool,
+    /// When the last generation was computed, to time the animation.
+    stepped: Instant,
+    /// Cell state painted by the current stroke and the last hex it touched.
+    brush: Option<(bool, HexInt)>,
+    /// Drags paint cells instead of panning while this is set.
+    painting: bool,
+    patterns: Vec<Pattern>,
+    picker: Picker,
+    panel: SettingsPanel,
+    /// Whether the soft keyboard was last asked to show.
+    keyboard: bool,
+    /// A share waiting for the activity, which input handling doesn't have.
+    share: Option<Share>,
+    stamp: Option<Stamp>,
+    /// Orientation stamps are placed in, kept between picks.
+    rotations: u8,
+    mirror: bool,
+    /// Drags select instead of panning or painting while this is set.
+    select: Option<SelectShape>,
+    selection: Option<Selection>,
+    clipboard: Option<Pattern>,
+    /// Generations run on the GPU since the board was last read back, while
+    /// the GPU is simulating.
+    gpu: Option<u64>,
+    /// Set when the board changed since the instances were last built. Edits
+    /// set it, which also calls for a frame.
+    dirty: bool,
+    /// Set when something on screen changed and a frame is due, even while
+    /// paused.
+    redraw: bool,
+}
+impl App {
+    fn new(data_dir: Option<PathBuf>, patterns: Vec<Pattern>) -> Self {
+        let settings = load_settings(data_dir.as_deref());
+        let mut game = HexGOL::new(settings.board_size);
+        game.set_rule(settings.rule);
+        game.set_neighborhood(settings.neighborhood);
+        game.set_topology(settings.topology);
+        game.randomize();
+        let mut stats = Stats::new(STATS_CAPACITY);
+        stats.record(&game);
+        let mut activity = Activity::new(ACTIVITY_WINDOW);
+        activity.record(&game);
+        let custom = load_theme(data_dir.as_deref());
+        let theme = match &settings.theme {
+            Some(name) => {
+                Theme::by_name(name).or_else(|| custom.clone().filter(|theme| theme.name == *name))
+            }
+            None => custom.clone(),
+        }
+        .unwrap_or_default();
+        let mut themes = Theme::builtin();
+        themes.extend(custom);
+
+        Self {
+            game,
+            camera: Camera::default(),
+            gestures: Gestures::new(),
+            renderer: None,
+            data_dir,
+            resumed: false,
+            running: true,
+            on_cycle: OnCycle::Pause,
+            history: History::new(settings.history_budget()),
+            stats,
+            activity,
+            view: View::Cells,
+            show_graph: true,
+            show_grid: true,
+            show_hud: true,
+            insets: Insets::default(),
+            frames: FrameCounter::new(),
+            backend: settings.backend,
+            settings,
+            themes,
+            theme,
+            animate: true,
+            stepped: Instant::now(),
+            brush: None,
+            painting: false,
+            patterns,
+            picker: Picker::new(),
+            panel: SettingsPanel::new(),
+            keyboard: false,
+            share: None,
+            stamp: None,
+            rotations: 0,
+            mirror: false,
+            select: None,
+            selection: None,
+            clipboard: None,
+            gpu: None,
+            dirty: true,
+            redraw: true,
+        }
+    }
+
+    /// Whether the main loop should keep spinning instead of blocking on the
+    /// looper: while ticking, touched, mid-transition or with a frame due.
+    fn animating(&self) -> bool {
+        if !self.resumed || self.renderer.is_none() {
+            return false;
+        }
+        let transition = self.animate && self.stepped.elapsed() < self.settings.tick();
+        self.running || self.dirty || self.redraw || self.gestures.active() || transition
+    }
+
+    fn tick(&mut self) {
+        // Generations recorded mid-stroke would split the edit in the history.
+        if !self.running || self.history.editing() {
+            return;
+        }
+        if self.gpu.is_some() {
+            self.step_gpu();
+        } else {
+            self.step();
+        }
+        self.stepped = Instant::now();
+    }
+    fn step(&mut self) {
+        let cycle = self.game.update();
+        self.history.record_generation(&self.game);
+        self.stats.record(&self.game);
+        self.activity.record(&self.game);
+        self.dirty = true;
+
+        if let Some(cycle) = cycle {
+            log::info!(
+                "Settled into a period {} cycle at generation {}",
+                cycle.period,
+                cycle.start
+            );
+            self.take_census();
+            match self.on_cycle {
+                OnCycle::Continue => {}
+                OnCycle::Pause => self.running = false,
+                OnCycle::Reseed => self.reseed(),
+            }
+        }
+    }
+    fn step_gpu(&mut self) {
+        if let (Some(renderer), Some(generations)) = (&mut self.renderer, &mut self.gpu) {
+            let life = match &renderer.life {
+                Some(life) => life,
+                None => return,
+            };
+            life.step(
+                renderer.gfx.context(),
+                &mut renderer.board,
+                self.game.rule(),
+                self.game.neighborhood(),
+                self.game.topology(),
+                1,
+            );
+            *generations += 1;
+        }
+    }
+    /// Hands the simulation to the GPU, or takes it back.
+    fn toggle_gpu(&mut self) {
+        if self.gpu.is_some() {
+            self.leave_gpu();
+            return;
+        }
+
+        if let Some(renderer) = &mut self.renderer {
+            if renderer.life.is_none() {
+                log::warn!("This device can't simulate on the GPU, staying on the CPU");
+                return;
+            }
+            renderer
+                .board
+                .update(renderer.gfx.context(), live_cells(&self.game));
+            self.gpu = Some(0);
+            log::info!("Simulating on the GPU");
+        }
+    }
+    /// Reads the board back from the GPU into the game. The generations run
+    /// there become a single edit in the history.
+    fn leave_gpu(&mut self) {
+        let generations = match self.gpu.take() {
+            Some(generations) => generations,
+            None => return,
+        };
+        let renderer = match &self.renderer {
+            Some(renderer) => renderer,
+            None => return,
+        };
+
+        let live: HashSet<HexInt> = match renderer.board.read(renderer.gfx.context()) {
+            Some(live) => live.into_iter().map(|[q, r]| HexInt::new(q, r)).collect(),
+            None => {
+                log::warn!("{} generations run on the GPU were lost", generations);
+                self.dirty = true;
+                return;
+            }
+        };
+        let changed: Vec<HexInt> = self
+            .game
+            .iter()
+            .filter(|(hex, cell)| **cell != live.contains(hex))
+            .map(|(hex, _cell)| *hex)
+            .collect();
+
+        self.game.flip(&changed);
+        self.game
+            .set_generation(self.game.generation() + generations);
+        self.record_edit(changed);
+        log::info!("Back on the CPU after {} generations", generations);
+    }
+    /// Drops the renderer if its device was lost, returning whether it was.
+    /// Generations simulated on the GPU since the board was last read back
+    /// are lost with it.
+    fn drop_lost_renderer(&mut self) -> bool {
+        if !matches!(&self.renderer, Some(renderer) if renderer.gfx.lost()) {
+            return false;
+        }
+        log::warn!("Lost the GPU, starting it over");
+        if let Some(generations) = self.gpu.take() {
+            log::warn!("{} generations run on the GPU were lost", generations);
+        }
+        self.renderer = None;
+        true
+    }
+
+    unsafe fn update_insets(&mut self, activity: *mut GameActivity) {
+        if let Some([left, top, right, bottom]) = java::window_insets(activity) {
+            self.insets = Insets {
+                left: left as f32,
+                top: top as f32,
+                right: right as f32,
+                bottom: bottom as f32,
+            };
+            // The settings panel keeps inside them too.
+            self.dirty = true;
+        }
+    }
+
+    /// Switches to the theme `steps` along the list, and remembers it.
+    fn cycle_theme(&mut self, steps: isize) {
+        self.theme = step_option(&self.themes, &self.theme, steps);
+        log::info!("Theme: {}", self.theme.name);
+
+        if let Some(renderer) = &mut self.renderer {
+            renderer.apply_theme(&self.theme);
+        }
+        self.settings.theme = Some(self.theme.name.clone());
+        self.save_settings();
+        self.dirty = true;
+    }
+
+    /// Steps a setting through its options, applies it and saves the settings.
+    /// The board size is only read at startup, and what's shown and what
+    /// happens on a cycle last until the app closes.
+    fn change_setting(&mut self, setting: Setting, steps: isize) {
+        match setting {
+            Setting::Rule => {
+                self.settings.rule = step_option(&RULES, &self.game.rule(), steps);
+                self.game.set_rule(self.settings.rule);
+            }
+            Setting::Neighborhood => {
+                self.settings.neighborhood =
+                    step_option(&Neighborhood::ALL, &self.game.neighborhood(), steps);
+                self.game.set_neighborhood(self.settings.neighborhood);
+            }
+            Setting::Topology => {
+                self.settings.topology = step_option(&Topology::ALL, &self.game.topology(), steps);
+                self.game.set_topology(self.settings.topology);
+            }
+            Setting::Speed => {
+                self.settings.speed = step_option(&SPEEDS, &self.settings.speed, steps);
+            }
+            Setting::OnCycle => self.on_cycle = step_option(&OnCycle::ALL, &self.on_cycle, steps),
+            Setting::Theme => return self.cycle_theme(steps),
+            Setting::View => self.view = step_option(&View::ALL, &self.view, steps),
+            Setting::Animation => self.animate = !self.animate,
+            Setting::Grid => self.show_grid = !self.show_grid,
+            Setting::Graph => self.show_graph = !self.show_graph,
+            Setting::BoardSize => {
+                self.settings.board_size =
+                    step_option(&BOARD_SIZES, &self.settings.board_size, steps);
+            }
+            Setting::History => {
+                self.settings.history = step_option(&HISTORY_SIZES, &self.settings.history, steps);
+                self.history.set_budget(self.settings.history_budget());
+            }
+            Setting::Backend => {
+                self.settings.backend =
+                    step_option(&GraphicsBackend::ALL, &self.settings.backend, steps);
+            }
+        }
+        log::info!("{:?}: {}", setting, self.setting_value(setting));
+        self.save_settings();
+        self.dirty = true;
+    }
+    fn setting_value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Rule => self.game.rule().to_string(),
+            Setting::Neighborhood => self.game.neighborhood().name().to_string(),
+            Setting::Topology => self.game.topology().name().to_string(),
+            Setting::Speed => format!("{}/s", self.settings.speed),
+            Setting::OnCycle => format!("{:?}", self.on_cycle),
+            Setting::Theme => self.theme.name.clone(),
+            Setting::View => format!("{:?}", self.view),
+            Setting::Animation => on_off(self.animate),
+            Setting::Grid => on_off(self.show_grid),
+            Setting::Graph => on_off(self.show_graph),
+            Setting::BoardSize => self.settings.board_size.to_string(),
+            Setting::History => format!("{} MiB", self.settings.history),
+            Setting::Backend => self.settings.backend.name().to_string(),
+        }
+    }
+    /// Applies the rule being typed, if it parses.
+    fn apply_rule_entry(&mut self) {
+        let rule = match self.panel.entry.as_ref().map(RuleEntry::rule) {
+            Some(Ok(rule)) => rule,
+            Some(Err(err)) => {
+                log::warn!("Not applying the rule: {}", err);
+                return;
+            }
+            None => return,
+        };
+        self.panel.entry = None;
+        self.apply_rule(rule);
+    }
+    /// Switches to a rule and remembers it, like picking it in the panel.
+    fn apply_rule(&mut self, rule: Rule) {
+        self.settings.rule = rule;
+        self.game.set_rule(rule);
+        log::info!("Rule: {}", rule);
+        self.save_settings();
+        self.dirty = true;
+    }
+    /// Takes the text the keyboard is editing as the rule being typed.
+    fn type_rule(&mut self, text: String) {
+        let entry = match &mut self.panel.entry {
+            Some(entry) => entry,
+            None => return,
+        };
+        // Keyboards without an enter key put a newline in instead.
+        match text.split_once('\n') {
+            Some((text, _rest)) => {
+                entry.text = text.to_string();
+                self.apply_rule_entry();
+            }
+            None => entry.text = text,
+        }
+        self.dirty = true;
+    }
+    /// Shows the soft keyboard while a rule is being typed and hides it after.
+    unsafe fn sync_keyboard(&mut self, activity: *mut GameActivity) {
+        match (&self.panel.entry, self.keyboard) {
+            (Some(entry), false) => keyboard::show(activity, &entry.text),
+            (None, true) => keyboard::hide(activity),
+            _ => return,
+        }
+        self.keyboard = !self.keyboard;
+    }
+
+    /// Writes the settings to the data directory.
+    fn save_settings(&self) {
+        if let Some(dir) = &self.data_dir {
+            let path = dir.join(SETTINGS_FILE);
+            if let Err(err) = std::fs::write(&path, self.settings.to_text()) {
+                log::warn!("Couldn't write {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Logs the census and saves it to `census.txt` in the data directory.
+    fn take_census(&self) {
+        let text = Census::take(&self.game).to_text();
+        for line in text.lines() {
+            log::info!("{}", line);
+        }
+
+        if let Some(dir) = &self.data_dir {
+            let path = dir.join("census.txt");
+            if let Err(err) = std::fs::write(&path, &text) {
+                log::warn!("Couldn't write {}: {}", path.display(), err);
+            }
+        }
+    }
+    /// Saves the board as `board.png` and `board.svg` in the export
+    /// directory, in the current theme and with the grid if it's showing.
+    /// Returns where the PNG went.
+    fn export_board(&self) -> Option<PathBuf> {
+        let dir = self.data_dir.as_ref()?.join(EXPORT_DIR);
+        if let Err(err) = std::fs::create_dir_all(&dir) {
+            log::warn!("Couldn't create {}: {}", dir.display(), err);
+            return None;
+        }
+        let options = ExportOptions {
+            grid: self.show_grid,
+            ..ExportOptions::default()
+        };
+        let write = |name: &str, bytes: Vec<u8>| {
+            let path = dir.join(name);
+            match std::fs::write(&path, bytes) {
+                Ok(()) => {
+                    log::info!("Exported {}", path.display());
+                    Some(path)
+                }
+                Err(err) => {
+                    log::warn!("Couldn't write {}: {}", path.display(), err);
+                    None
+                }
+            }
+        };
+        let png = write(
+            "board.png",
+            encode_png(&rasterize(&self.game, &self.theme, &options)),
+        );
+        write(
+            "board.svg",
+            to_svg(&self.game, &self.theme, &options).into_bytes(),
+        );
+        png
+    }
+
+    /// The selection as a pattern, or the whole board without one, under the
+    /// current rule.
+    fn shared_pattern(&self) -> Pattern {
+        let mut pattern = match self.selection {
+            Some(selection) => self.game.copy(&selection),
+            None => Pattern::new(
+                String::new(),
+                None,
+                live_cells(&self.game)
+                    .map(|[q, r]| HexInt::new(q, r))
+                    .collect(),
+            ),
+        };
+        pattern.rule = Some(self.game.rule());
+        pattern
+    }
+    /// Opens the share sheet for a share asked for since the last call.
+    unsafe fn send_share(&mut self, activity: *mut GameActivity) {
+        match self.share.take() {
+            Some(Share::Image) => {
+                if let Some(path) = self.export_board() {
+                    java::share_file(activity, &path, "image/png");
+                }
+            }
+            Some(Share::Pattern) => java::share_text(activity, &self.shared_pattern().to_text()),
+            None => {}
+        }
+    }
+    /// Floats a pattern file another app opened the game with as a stamp, to
+    /// be placed like one from the library.
+    unsafe fn open_shared(&mut self, activity: *mut GameActivity) {
+        let text = match java::take_opened_file(activity) {
+            Some(text) => text,
+            None => return,
+        };
+        match Pattern::parse(&text) {
+            Ok(pattern) => {
+                log::info!("Opened {}", pattern.name);
+                self.stamp = Some(Stamp {
+                    pattern,
+                    picked: None,
+                    anchor: None,
+                    twist: 0.0,
+                });
+                self.picker.open = false;
+                self.panel.open = false;
+                self.dirty = true;
+            }
+            Err(err) => log::warn!("Couldn't open the pattern: {}", err),
+        }
+    }
+    fn reseed(&mut self) {
+        self.history.begin_edit();
+        self.game.randomize();
+        for hex in self.game.changed() {
+            self.history.record_edit(*hex);
+        }
+        self.end_edit();
+    }
+    /// Closes the edit in progress and samples the board it left.
+    fn end_edit(&mut self) {
+        self.history.end_edit();
+        self.resample();
+    }
+    /// Samples the board again after it changed other than by stepping, so
+    /// the graph and the heatmap don't show a board that's gone.
+    fn resample(&mut self) {
+        self.stats.resample(&self.game);
+        self.activity.record(&self.game);
+        self.dirty = true;
+    }
+
+    /// Steps back, pausing so the next generation doesn't throw away what
+    /// can be redone.
+    fn undo(&mut self) {
+        self.running = false;
+        if self.history.undo(&mut self.game).is_some() {
+            self.resample();
+        }
+    }
+    fn redo(&mut self) {
+        self.running = false;
+        if self.history.redo(&mut self.game).is_some() {
+            self.resample();
+        }
+    }
+
+    /// Paints every hex between the last brush position and `hex`.
+    fn paint(&mut self, hex: HexInt) {
+        if let Some((alive, last)) = self.brush {
+            for hex in last.line_to(&hex) {
+                if self.game.set(&hex, alive) {
+                    self.history.record_edit(hex);
+                    self.dirty = true;
+                }
+            }
+            self.brush = Some((alive, hex));
+        }
+    }
+
+    /// Records cells flipped outside the brush as one edit.
+    fn record_edit(&mut self, cells: Vec<HexInt>) {
+        self.history.begin_edit();
+        for hex in cells {
+            self.history.record_edit(hex);
+        }
+        self.end_edit();
+    }
+
+    /// Turns the stamp if there is one, otherwise the selected cells.
+    fn rotate(&mut self, rotations: u8) {
+        if self.stamp.is_some() {
+            self.rotations = (self.rotations + rotations) % 6;
+            self.dirty = true;
+        } else if let Some(selection) = self.selection {
+            let cells = self.game.rotate(&selection, rotations);
+            self.record_edit(cells);
+        }
+    }
+    fn mirror(&mut self) {
+        if self.stamp.is_some() {
+            self.mirror = !self.mirror;
+            self.dirty = true;
+        } else if let Some(selection) = self.selection {
+            let cells = self.game.mirror(&selection);
+            self.record_edit(cells);
+        }
+    }
+
+    fn copy(&mut self) {
+        if let Some(selection) = self.selection {
+            let pattern = self.game.copy(&selection);
+            log::info!("Copied {} cells", pattern.cells.len());
+            self.clipboard = Some(pattern);
+        }
+    }
+    fn cut(&mut self) {
+        if let Some(selection) = self.selection {
+            let (pattern, cells) = self.game.cut(&selection);
+            log::info!("Cut {} cells", pattern.cells.len());
+            self.clipboard = Some(pattern);
+            self.record_edit(cells);
+        }
+    }
+    /// Floats the clipboard as a stamp over the selection, or wherever is tapped next.
+    fn paste(&mut self) {
+        if let Some(pattern) = &self.clipboard {
+            self.stamp = Some(Stamp {
+                pattern: pattern.clone(),
+                picked: None,
+                anchor: self.selection.map(|selection| selection.center()),
+                twist: 0.0,
+            });
+            self.rotations = 0;
+            self.mirror = false;
+            self.dirty = true;
+        }
+    }
+    fn clear(&mut self) {
+        if let Some(selection) = self.selection {
+            let cells = self.game.clear(&selection);
+            self.record_edit(cells);
+        }
+    }
+    fn toggle_painting(&mut self) {
+        self.painting = !self.painting;
+        if self.painting {
+            self.select = None;
+            self.selection = None;
+        }
+        log::info!("Painting: {}", self.painting);
+        self.dirty = true;
+    }
+    fn cycle_select(&mut self) {
+        self.painting = false;
+        self.select = match self.select {
+            None => Some(SelectShape::Rect),
+            Some(SelectShape::Rect) => Some(SelectShape::Hex),
+            Some(SelectShape::Hex) => None,
+        };
+        if self.select.is_none() {
+            self.selection = None;
+        }
+        log::info!("Select: {:?}", self.select);
+        self.dirty = true;
+    }
+
+    /// Cells the stamp would bring to life, on the board.
+    fn stamp_cells(&self) -> Vec<HexInt> {
+        match &self.stamp {
+            Some(Stamp {
+                pattern,
+                anchor: Some(anchor),
+                ..
+            }) => pattern
+                .transformed(self.rotations, self.mirror)
+                .into_iter()
+                .map(|hex| hex + *anchor)
+                .collect(),
+            _ => vec![],
+        }
+    }
+    /// Places the stamp as a single edit, switching to the pattern's rule.
+    fn place_stamp(&mut self) {
+        let cells = self.stamp_cells();
+        let stamp = match &mut self.stamp {
+            Some(stamp) if stamp.anchor.is_some() => stamp,
+            _ => return,
+        };
+        stamp.anchor = None;
+
+        if let Some(rule) = stamp.pattern.rule.filter(|rule| *rule != self.game.rule()) {
+            log::info!("{} runs under {}, switching rule", stamp.pattern.name, rule);
+            self.apply_rule(rule);
+        }
+
+        let cells = self.game.paste(&cells);
+        self.record_edit(cells);
+    }
+    fn pick(&mut self, hit: PickerHit) {
+        match hit {
+            PickerHit::Toggle => {
+                if self.picker.open {
+                    self.picker.open = false;
+                } else if self.stamp.is_some() {
+                    self.stamp = None;
+                } else {
+                    self.picker.open = true;
+                }
+            }
+            PickerHit::Rotate => self.rotate(1),
+            PickerHit::Mirror => self.mirror(),
+            PickerHit::Paint => self.toggle_painting(),
+            PickerHit::Select => self.cycle_select(),
+            PickerHit::Copy => self.copy(),
+            PickerHit::Cut => self.cut(),
+            PickerHit::Paste => {
+                self.paste();
+                self.picker.open = false;
+            }
+            PickerHit::Clear => self.clear(),
+            PickerHit::Share => {
+                self.share = Some(Share::Image);
+                self.picker.open = false;
+            }
+            PickerHit::SharePattern => {
+                self.share = Some(Share::Pattern);
+                self.picker.open = false;
+            }
+            PickerHit::Census => {
+                self.take_census();
+                self.picker.open = false;
+            }
+            PickerHit::Settings => {
+                self.panel.open = true;
+                self.picker.open = false;
+            }
+            PickerHit::Pattern(picked) => {
+                log::info!("Stamping {}", self.patterns[picked].name);
+                self.stamp = Some(Stamp {
+                    pattern: self.patterns[picked].clone(),
+                    picked: Some(picked),
+                    anchor: None,
+                    twist: 0.0,
+                });
+                self.picker.open = false;
+            }
+        }
+        self.dirty = true;
+    }
+
+    fn hex_at(&self, pos: [f32; 2], size: [u32; 2]) -> HexInt {
+        let world = self.camera.screen_to_world(pos, size);
+        HexInt::from(HexFract::inv_transform(&world, 1.0).round())
+    }
+
+    fn handle_key(&mut self, key_code: u32, meta_state: u32) {
+        // Typing goes through the keyboard's text, not key presses.
+        if self.panel.entry.is_some() {
+            match key_code {
+                AKEYCODE_ENTER => self.apply_rule_entry(),
+                AKEYCODE_ESCAPE => {
+                    self.panel.entry = None;
+                    self.dirty = true;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let ctrl = meta_state & AMETA_CTRL_ON != 0;
+        let shift = meta_state & AMETA_SHIFT_ON != 0;
+
+        // Anything that reads or edits the board needs it back on the CPU.
+        if !matches!(
+            key_code,
+            AKEYCODE_SPACE
+                | AKEYCODE_A
+                | AKEYCODE_G
+                | AKEYCODE_H
+                | AKEYCODE_I
+                | AKEYCODE_MENU
+                | AKEYCODE_O
+                | AKEYCODE_T
+                | AKEYCODE_U
+        ) {
+            self.leave_gpu();
+        }
+
+        match key_code {
+            AKEYCODE_SPACE => self.running = !self.running,
+            AKEYCODE_A => self.change_setting(Setting::Animation, 1),
+            AKEYCODE_O => self.change_setting(Setting::View, 1),
+            AKEYCODE_R => self.reseed(),
+            AKEYCODE_G => self.change_setting(Setting::Graph, 1),
+            AKEYCODE_H => self.change_setting(Setting::Grid, 1),
+            AKEYCODE_I => self.show_hud = !self.show_hud,
+            AKEYCODE_T => self.cycle_theme(1),
+            AKEYCODE_MENU => {
+                self.panel.open = !self.panel.open;
+                self.dirty = true;
+            }
+            AKEYCODE_K => self.take_census(),
+            AKEYCODE_E if shift => self.share = Some(Share::Image),
+            AKEYCODE_E if ctrl => self.share = Some(Share::Pattern),
+            AKEYCODE_E => {
+                self.export_board();
+            }
+            AKEYCODE_U => self.toggle_gpu(),
+            AKEYCODE_C if ctrl => self.copy(),
+            AKEYCODE_X if ctrl => self.cut(),
+            AKEYCODE_V if ctrl => self.paste(),
+            AKEYCODE_DEL | AKEYCODE_FORWARD_DEL => self.clear(),
+            AKEYCODE_S => self.cycle_select(),
+            AKEYCODE_C => self.change_setting(Setting::OnCycle, 1),
+            AKEYCODE_Z if ctrl && shift => self.redo(),
+            AKEYCODE_Z if ctrl => self.undo(),
+            AKEYCODE_Y if ctrl => self.redo(),
+            AKEYCODE_P => self.pick(PickerHit::Toggle),
+            AKEYCODE_LEFT_BRACKET => self.rotate(1),
+            AKEYCODE_RIGHT_BRACKET => self.rotate(5),
+            AKEYCODE_M => self.mirror(),
+            AKEYCODE_ENTER => self.place_stamp(),
+            AKEYCODE_ESCAPE => {
+                self.stamp = None;
+                self.selection = None;
+                self.picker.open = false;
+                self.panel.open = false;
+                self.dirty = true;
+            }
+            AKEYCODE_B => self.toggle_painting(),
+            AKEYCODE_DPAD_LEFT => self.undo(),
+            AKEYCODE_DPAD_RIGHT => {
+                if self.history.can_redo() {
+                    self.redo();
+                } else {
+                    self.running = false;
+                    self.step();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_gesture(&mut self, gesture: Gesture) {
+        let size = match &self.renderer {
+            Some(renderer) => renderer.gfx.size(),
+            None => return,
+        };
+
+        // The panel takes every tap while it's open, and the board stays put.
+        if self.panel.open {
+            if let Gesture::Tap { pos, fingers: 1 } = gesture {
+                let hit = self.panel.hit(pos, size, self.insets);
+                // Tapping anywhere but the rule leaves the rule unchanged.
+                if hit != Some(PanelHit::RuleValue) && self.panel.entry.is_some() {
+                    self.panel.entry = None;
+                    self.dirty = true;
+                }
+                match hit {
+                    Some(PanelHit::Close) => {
+                        self.panel.open = false;
+                        self.dirty = true;
+                    }
+                    Some(PanelHit::Step(setting, steps)) => self.change_setting(setting, steps),
+                    Some(PanelHit::RuleValue) => {
+                        if self.panel.entry.is_none() {
+                            self.panel.entry = Some(RuleEntry {
+                                text: self.game.rule().to_string(),
+                            });
+                        }
+                        // Brings the keyboard back if it was dismissed.
+                        self.keyboard = false;
+                        self.dirty = true;
+                    }
+                    Some(PanelHit::Panel) | None => {}
+                }
+            }
+            return;
+        }
+
+        // Without a tool to drag, one finger pans the board like two do.
+        let panning = !self.painting && self.stamp.is_none() && self.select.is_none();
+        let moves_camera = match gesture {
+            Gesture::Pinch { .. } => true,
+            Gesture::DragStart { .. } | Gesture::Drag { .. } | Gesture::DragEnd => panning,
+            Gesture::Tap { .. } => false,
+        };
+        if !moves_camera {
+            self.leave_gpu();
+        }
+
+        match gesture {
+            Gesture::Tap { pos, fingers: 1 } => {
+                if let Some(hit) = self.picker.hit(pos, self.patterns.len(), size) {
+                    self.pick(hit);
+                    return;
+                }
+
+                let hex = self.hex_at(pos, size);
+                if let Some(stamp) = &mut self.stamp {
+                    if stamp.anchor == Some(hex) {
+                        self.place_stamp();
+                    } else {
+                        stamp.anchor = Some(hex);
+                        self.dirty = true;
+                    }
+                } else if self.select.is_some() {
+                    self.selection = None;
+                    self.dirty = true;
+                } else if let Some(&alive) = self.game.get(&hex) {
+                    self.history.begin_edit();
+                    self.brush = Some((!alive, hex));
+                    self.paint(hex);
+                    self.brush = None;
+                    self.end_edit();
+                }
+            }
+            Gesture::Tap { fingers: 2, .. } => self.undo(),
+            Gesture::Tap { fingers: 3, .. } => self.redo(),
+            Gesture::DragStart { pos } | Gesture::Drag { pos, .. } if self.stamp.is_some() => {
+                let hex = self.hex_at(pos, size);
+                if let Some(stamp) = &mut self.stamp {
+                    stamp.anchor = Some(hex);
+                }
+                self.dirty = true;
+            }
+            Gesture::DragStart { pos } if self.select.is_some() => {
+                let hex = self.hex_at(pos, size);
+                self.selection = match self.select {
+                    Some(SelectShape::Rect) => Some(Selection::Rect { a: hex, b: hex }),
+                    _ => Some(Selection::Hex {
+                        center: hex,
+                        radius: 0,
+                    }),
+                };
+                self.dirty = true;
+            }
+            Gesture::Drag { pos, .. } if self.select.is_some() => {
+                let hex = self.hex_at(pos, size);
+                self.selection = match self.selection {
+                    Some(Selection::Rect { a, .. }) => Some(Selection::Rect { a, b: hex }),
+                    Some(Selection::Hex { center, .. }) => Some(Selection::Hex {
+                        center,
+                        radius: center.distance(&hex),
+                    }),
+                    None => None,
+                };
+                self.dirty = true;
+            }
+            Gesture::DragStart { pos } if self.painting => {
+                let hex = self.hex_at(pos, size);
+                if let Some(&alive) = self.game.get(&hex) {
+                    self.history.begin_edit();
+                    self.brush = Some((!alive, hex));
+                    self.paint(hex);
+                }
+            }
+            Gesture::Drag { pos, .. } if self.painting => {
+                let hex = self.hex_at(pos, size);
+                self.paint(hex);
+            }
+            Gesture::Drag { delta, .. } if panning => self.camera.pan(delta, size),
+            Gesture::DragEnd if self.brush.take().is_some() => self.end_edit(),
+            Gesture::Pinch {
+                focus,
+                delta,
+                scale,
+                rotation,
+            } => {
+                self.camera.pan(delta, size);
+                self.camera.zoom_at(scale, focus, size);
+
+                // Twisting past half a step turns the stamp by one.
+                use std::f32::consts::FRAC_PI_6;
+                let mut steps = 0;
+                if let Some(stamp) = &mut self.stamp {
+                    stamp.twist += rotation;
+                    while stamp.twist > FRAC_PI_6 {
+                        stamp.twist -= 2.0 * FRAC_PI_6;
+                        steps += 1;
+                    }
+                    while stamp.twist < -FRAC_PI_6 {
+                        stamp.twist += 2.0 * FRAC_PI_6;
+                        steps += 5;
+                    }
+                }
+                if steps > 0 {
+                    self.rotate(steps);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self) {
+        let stamp_cells = self.stamp_cells();
+        let values: Vec<String> = Setting::ALL
+            .iter()
+            .map(|setting| self.setting_value(*setting))
+            .collect();
+        let renderer = match &mut self.renderer {
+            Some(renderer) => renderer,
+            None => return,
+        };
+
+        self.redraw = false;
+        renderer.gfx.update(&self.camera);
+        self.frames.frame();
+        let progress = self.stepped.elapsed().as_secs_f32() / self.settings.tick().as_secs_f32();
+        renderer
+            .board
+            .set_progress(renderer.gfx.context(), self.animate.then_some(progress));
+
+        if self.dirty {
+            self.dirty = false;
+
+            // While simulating there, the GPU holds the board.
+            if self.gpu.is_none() {
+                let ctx = renderer.gfx.context();
+                if self.game.generation() != renderer.generation {
+                    renderer.board.advance(ctx, live_cells(&self.game));
+                } else {
+                    renderer.board.update(ctx, live_cells(&self.game));
+                }
+                renderer.generation = self.game.generation();
+            }
+
+            // The selection is tinted and the stamp floats above it.
+            let mut preview = vec![];
+            if let Some(selection) = &self.selection {
+                for (hex, _cell) in self.game.iter() {
+                    if selection.contains(hex) {
+                        preview.push(
+                            Instance::new(
+                                HexFract::from(*hex).transform(1.0),
+                                [1.0, 1.0],
+                                self.theme.selection,
+                            )
+                            .with_alpha(0.25),
+                        );
+                    }
+                }
+            }
+            let outline: Vec<Instance> = stamp_cells
+                .iter()
+                .map(|hex| {
+                    Instance::new(
+                        HexFract::from(*hex).transform(1.0),
+                        [1.0, 1.0],
+                        self.theme.preview,
+                    )
+                })
+                .collect();
+            preview.extend(outline.iter().map(|instance| instance.with_alpha(0.6)));
+            renderer
+                .preview_instanced
+                .update(renderer.gfx.context(), &preview);
+            renderer
+                .preview_outline
+                .update(renderer.gfx.context(), &outline);
+
+            let (quads, hexes) = self.picker.instances(
+                &self.patterns,
+                self.stamp.as_ref().and_then(|stamp| stamp.picked),
+                &[
+                    (PickerHit::Paint, self.painting),
+                    (PickerHit::Select, self.select.is_some()),
+                ],
+                self.rotations,
+                self.mirror,
+                renderer.gfx.size(),
+            );
+            renderer.overlay.update(&renderer.gfx, &quads, &hexes);
+
+            let restart = self.settings.board_size != self.game.size()
+                || self.settings.backend != self.backend;
+            let note = restart.then_some("Some changes apply on restart");
+            let (quads, labels) =
+                self.panel
+                    .instances(&values, note, renderer.gfx.size(), self.insets);
+            renderer.panel.update(&renderer.gfx, &quads, &[]);
+            renderer.panel_text.update(&renderer.gfx, &labels);
+
+            let stats = &self.stats;
+            let flux = |s: &GenerationStats| s.births.max(s.deaths);
+            renderer.graph.update(
+                renderer.gfx.context(),
+                &[
+                    GraphSeries {
+                        values: stats.normalized(|s| s.population, |s| s.population),
+                        color: self.theme.live,
+                    },
+                    GraphSeries {
+                        values: stats.normalized(|s| s.radius, |s| s.radius),
+                        color: [0.3, 0.5, 1.0],
+                    },
+                    GraphSeries {
+                        values: stats.normalized(|s| s.births, flux),
+                        color: [0.3, 1.0, 0.3],
+                    },
+                    GraphSeries {
+                        values: stats.normalized(|s| s.deaths, flux),
+                        color: [1.0, 0.3, 0.3],
+                    },
+                ],
+            );
+
+            let (cells, style) = match self.view {
+                View::Cells => (Default::default(), HeatStyle::Heat),
+                View::Heatmap => (self.activity.heat(), HeatStyle::Heat),
+                View::Trails => (self.activity.trails(), HeatStyle::Trail(self.theme.live)),
+            };
+            renderer.heatmap.update(
+                renderer.gfx.context(),
+                cells
+                    .into_iter()
+                    .map(|(hex, value)| ([hex.q(), hex.r()], value)),
+                style,
+            );
+        }
+
+        let mut hud = Hud::new();
+        if self.show_hud {
+            match self.gpu {
+                Some(generations) => {
+                    hud.push(
+                        Corner::TopLeft,
+                        format!("Gen {}", self.game.generation() + generations),
+                    );
+                    hud.push(Corner::TopLeft, "On the GPU".to_string());
+                }
+                None => {
+                    let population = self.stats.iter().last().map_or(0, |s| s.population);
+                    hud.push(Corner::TopLeft, format!("Gen {}", self.game.generation()));
+                    hud.push(Corner::TopLeft, format!("Pop {}", population));
+                }
+            }
+            hud.push(Corner::BottomRight, format!("{:.0} fps", self.frames.fps()));
+            hud.push(Corner::BottomRight, self.game.rule().to_string());
+
+            hud.reserve(Corner::TopRight, Picker::height());
+            if !self.running {
+                hud.push(Corner::TopRight, "Paused".to_string());
+            }
+            if self.show_graph {
+                hud.reserve(Corner::BottomLeft, Graph::height(renderer.gfx.size()));
+            }
+            match self.view {
+                View::Cells => {}
+                View::Heatmap => hud.push(Corner::BottomLeft, "Heatmap".to_string()),
+                View::Trails => hud.push(Corner::BottomLeft, "Trails".to_string()),
+            }
+        }
+        renderer.text.update(
+            &renderer.gfx,
+            &hud.labels(renderer.gfx.size(), self.insets, self.theme.live),
+        );
+
+        let mut frame = match renderer.gfx.start_frame() {
+            Some(frame) => frame,
+            None => {
+                // Try again once the surface is back.
+                self.redraw = true;
+                return;
+            }
+        };
+        let mut render_pass = frame.begin_pass(&renderer.gfx);
+        if self.show_grid {
+            let pixels_per_cell = self.camera.pixels_per_unit(renderer.gfx.size());
+            renderer
+                .grid
+                .draw_lines(&renderer.gfx, &mut render_pass, pixels_per_cell);
+        }
+        renderer.heatmap.draw(&renderer.gfx, &mut render_pass);
+        // The heatmap already shows where the live cells are.
+        if self.view != View::Heatmap {
+            renderer.board.draw(&renderer.gfx, &mut render_pass);
+        }
+        renderer.grid.draw_border(&renderer.gfx, &mut render_pass);
+        renderer
+            .preview_instanced
+            .draw(&renderer.gfx, &mut render_pass);
+        renderer
+            .preview_outline
+            .draw(&renderer.gfx, &mut render_pass);
+        if self.show_graph {
+            renderer.graph.draw(&renderer.gfx, &mut render_pass);
+        }
+        renderer.text.draw(&renderer.gfx, &mut render_pass);
+        renderer.overlay.draw(&renderer.gfx, &mut render_pass);
+        renderer.panel.draw(&renderer.gfx, &mut render_pass);
+        renderer.panel_text.draw(&renderer.gfx, &mut render_pass);
+        drop(render_pass);
+        renderer.gfx.end_frame(frame);
+    }
+}
+
+fn on_off(on: bool) -> String {
+    if on { "On" } else { "Off" }.to_string()
+}
+
+fn live_cells(game: &HexGOL) -> impl Iterator<Item = [i32; 2]> + '_ {
+    game.iter()
+        .filter(|(_hex, cell)| **cell)
+        .map(|(hex, _cell)| [hex.q(), hex.r()])
+}
+
+use raw_window_handle::*;
+unsafe impl HasRawWindowHandle for android_app {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = AndroidNdkWindowHandle::empty();
+        handle.a_native_window = self.window as *mut c_void;
+        RawWindowHandle::AndroidNdk(handle)
+    }
+}
+unsafe impl HasRawDisplayHandle for android_app {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Android(AndroidDisplayHandle::empty())
+    }
+}
+
+unsafe extern "C" fn command(app: *mut android_app, cmd: i32) {
+    let state: *mut App = std::mem::transmute((*app).userData);
+
+    match cmd as u32 {
+        NativeAppGlueAppCmd_APP_CMD_INIT_WINDOW => {
+            create_renderer(app, &mut *state);
+            (*state).update_insets((*app).activity);
+        }
+        NativeAppGlueAppCmd_APP_CMD_TERM_WINDOW => {
+            (*state).leave_gpu();
+            (*state).renderer = None;
+        }
+        NativeAppGlueAppCmd_APP_CMD_WINDOW_RESIZED => {
+            if let Some(renderer) = &mut (*state).renderer {
+                renderer.gfx.resize([
+                    anativewindow_getwidth((*app).window) as u32,
+                    anativewindow_getheight((*app).window) as u32,
+                ]);
+            }
+            (*state).update_insets((*app).activity);
+            (*state).dirty = true;
+        }
+        NativeAppGlueAppCmd_APP_CMD_CONFIG_CHANGED => {
+            (*state).update_insets((*app).activity);
+            (*state).redraw = true;
+        }
+        NativeAppGlueAppCmd_APP_CMD_RESUME => {
+            (*state).resumed = true;
+            (*state).redraw = true;
+            // Opening a file with the game brings it to the front.
+            (*state).open_shared((*app).activity);
+        }
+        NativeAppGlueAppCmd_APP_CMD_PAUSE => {
+            (*state).resumed = false;
+        }
+        _ => {}
+    }
+}
+
+/// Sets up drawing to the window, or tells the player why it can't be.
+unsafe fn create_renderer(app: *mut android_app, state: &mut App) {
+    let size = [
+        anativewindow_getwidth((*app).window) as u32,
+        anativewindow_getheight((*app).window) as u32,
+    ];
+    match pollster::block_on(Graphics::new(size, &*app, SAMPLE_COUNT, state.backend)) {
+        Ok(gfx) => {
+            state.renderer = Some(Renderer::new(gfx, state.game.size(), &state.theme));
+            state.dirty = true;
+        }
+        Err(err) => {
+            log::error!("Can't render: {}", err);
+            java::show_error(
+                (*app).activity,
+                &format!("Hex GoL can't draw on this device: {}.", err),
+            );
+        }
+    }
+}
+
+unsafe fn process_input(app: *mut android_app, state: &mut App) {
+    let glue = &*glue::glue(app);
+    let mut gestures = vec![];
+
+    for event in &glue.motionEvents[..glue.motionEventsCount as usize] {
+        let action = event.action as u32 & AMOTION_EVENT_ACTION_MASK;
+        let index = ((event.action as u32 & AMOTION_EVENT_ACTION_POINTER_INDEX_MASK)
+            >> AMOTION_EVENT_ACTION_POINTER_INDEX_SHIFT) as usize;
+
+        let pointers: Vec<Pointer> = event.pointers[..event.pointerCount as usize]
+            .iter()
+            .map(|p| Pointer {
+                id: p.id,
+                pos: [
+                    p.axisValues[AMOTION_EVENT_AXIS_X as usize],
+                    p.axisValues[AMOTION_EVENT_AXIS_Y as usize],
+                ],
+            })
+            .collect();
+        let id = pointers.get(index).map_or(0, |p| p.id);
+
+        let phase = match action {
+            AMOTION_EVENT_ACTION_DOWN | AMOTION_EVENT_ACTION_POINTER_DOWN => TouchPhase::Down(id),
+            AMOTION_EVENT_ACTION_UP | AMOTION_EVENT_ACTION_POINTER_UP => TouchPhase::Up(id),
+            AMOTION_EVENT_ACTION_MOVE => TouchPhase::Move,
+            AMOTION_EVENT_ACTION_CANCEL => TouchPhase::Cancel,
+            _ => continue,
+        };
+        state.gestures.handle(phase, &pointers, &mut gestures);
+    }
+    // Whatever the input did, it's seen on the next frame.
+    if glue.motionEventsCount > 0 || glue.keyDownEventsCount > 0 {
+        state.redraw = true;
+    }
+    if glue.motionEventsCount > 0 {
+        glue::glue_clear_motion_events(app);
+    }
+
+    for event in &glue.keyDownEvents[..glue.keyDownEventsCount as usize] {
+        state.handle_key(event.keyCode as u32, event.metaState as u32);
+    }
+    if glue.keyDownEventsCount > 0 {
+        glue::glue_clear_key_down_events(app);
+    }
+    if glue.keyUpEventsCount > 0 {
+        glue::glue_clear_key_up_events(app);
+    }
+
+    for gesture in gestures {
+        state.handle_gesture(gesture);
+    }
+
+    if let Some(text) = keyboard::take_text(app) {
+        state.type_rule(text);
+        state.redraw = true;
+    }
+    state.sync_keyboard((*app).activity);
+    state.send_share((*app).activity);
+}
+
+unsafe fn load_patterns(manager: *mut ndk_sys::AAssetManager) -> Vec<Pattern> {
+    let mut patterns = vec![];
+    for name in assets::list(manager, PATTERN_DIR) {
+        let path = format!("{}/{}", PATTERN_DIR, name);
+        let text = match assets::read(manager, &path) {
+            Some(data) => String::from_utf8_lossy(&data).into_owned(),
+            None => continue,
+        };
+
+        match Pattern::parse(&text) {
+            Ok(mut pattern) => {
+                if pattern.name.is_empty() {
+                    pattern.name = name.trim_end_matches(".hexp").to_string();
+                }
+                patterns.push(pattern);
+            }
+            Err(err) => log::warn!("Skipping {}: {}", path, err),
+        }
+    }
+    patterns
+}
+
+/// The theme from the data directory, if there is one.
+fn load_theme(data_dir: Option<&std::path::Path>) -> Option<Theme> {
+    let path = data_dir?.join(THEME_FILE);
+    let text = std::fs::read_to_string(&path).ok()?;
+    Theme::parse(&text)
+        .map_err(|err| log::warn!("Ignoring {}: {}", path.display(), err))
+        .ok()
+}
+
+/// The settings saved in the data directory, or the defaults if there are none.
+fn load_settings(data_dir: Option<&std::path::Path>) -> Settings {
+    let path = match data_dir {
+        Some(dir) => dir.join(SETTINGS_FILE),
+        None => return Settings::default(),
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) => return Settings::default(),
+    };
+    Settings::parse(&text).unwrap_or_else(|err| {
+        log::warn!("Ignoring {}: {}", path.display(), err);
+        Settings::default()
+    })
+}
+
+unsafe fn alooper_pollall(
+    timeout: i32,
+    out_fd: *mut i32,
+    out_event: *mut i32,
+    out_data: *mut *mut c_void,
+) -> i32 {
+    ndk_sys::ALooper_pollAll(timeout, out_fd, out_event, out_data)
+}
+
+unsafe fn anativewindow_getwidth(window: *mut ndk_sys::ANativeWindow) -> i32 {
+    ndk_sys::ANativeWindow_getWidth(window)
+}
+unsafe fn anativewindow_getheight(window: *mut ndk_sys::ANativeWindow) -> i32 {
+    ndk_sys::ANativeWindow_getHeight(window)
+}
+
+/// Asset directory holding the pattern library.
+const PATTERN_DIR: &str = "patterns";
+/// Theme config in the data directory.
+const THEME_FILE: &str = "theme.conf";
+/// Settings file in the data directory.
+const SETTINGS_FILE: &str = "settings.conf";
+/// Directory in the data directory for exports, which can be shared with
+/// other apps.
+const EXPORT_DIR: &str = "exports";
+
+/// Generations shown in the statistics graph.
+const STATS_CAPACITY: usize = 256;
+/// Generations the heatmap and trails look back over.
+const ACTIVITY_WINDOW: usize = 64;
+
+/// Multisampling requested for rendering; 1 turns it off.
+const SAMPLE_COUNT: u32 = 4;
+
+/// Upper bound on ticks run per frame so a long stall doesn't snowball.
+const MAX_TICKS_PER_FRAME: u32 = 4;
+
+#[no_mangle]
+pub unsafe extern "C" fn android_main(app: *mut android_app) {
+    android_logger::init_once(
+        android_logger::Config::default()
+            .with_tag("Rust")
+            .with_min_level(log::Level::Info),
+    );
+
+    let data_dir = (*(*app).activity).internalDataPath;
+    let data_dir = if data_dir.is_null() {
+        None
+    } else {
+        Some(PathBuf::from(
+            std::ffi::CStr::from_ptr(data_dir)
+                .to_string_lossy()
+                .as_ref(),
+        ))
+    };
+
+    let patterns = load_patterns((*(*app).activity).assetManager);
+    log::info!("Loaded {} patterns", patterns.len());
+
+    let state = Box::into_raw(Box::new(App::new(data_dir, patterns)));
+    (*app).userData = state as *mut c_void;
+    (*app).onAppCmd = Some(command);
+
+    let mut events: i32 = 0;
+    let mut poll_source: *mut android_poll_source = std::ptr::null_mut();
+
+    let mut clock = Instant::now();
+    let mut lag = Duration::ZERO;
+
+    'main: loop {
+        loop {
+            // Block until something happens while there is nothing to draw.
+            // Input wakes the looper too.
+            let timeout = if (*state).animating() { 0 } else { -1 };
+            let result = alooper_pollall(
+                timeout,
+                std::ptr::null_mut(),
+                addr_of_mut!(events),
+                addr_of_mut!(poll_source) as *mut *mut c_void,
+            );
+            if timeout < 0 {
+                // Don't try to catch up on the time spent asleep, however the
+                // looper woke up.
+                clock = Instant::now();
+            }
+            if result < 0 {
+                break;
+            }
+
+            if !poll_source.is_null() {
+                (*poll_source).process.unwrap()(app, poll_source);
+            }
+            if (*app).destroyRequested > 0 {
+                break 'main;
+            }
+        }
+
+        process_input(app, &mut *state);
+
+        let now = Instant::now();
+        lag += now - clock;
+        clock = now;
+
+        let tick = (*state).settings.tick();
+        let mut ticks = 0;
+        while lag >= tick {
+            lag -= tick;
+            (*state).tick();
+
+            ticks += 1;
+            if ticks == MAX_TICKS_PER_FRAME {
+                lag = Duration::ZERO;
+            }
+        }
+
+        // Presenting with `PresentMode::Fifo` waits for vsync, which paces the loop.
+        (*state).draw();
+
+        if (*state).drop_lost_renderer() && !(*app).window.is_null() {
+            create_renderer(app, &mut *state);
+        }
+    }
+
+    (*app).userData = std::ptr::null_mut();
+    drop(Box::from_raw(state));
+}
+
+use jni::sys::*;
+
+// Rust doesn't give us a clean way to directly export symbols from C/C++
+// so we rename the C/C++ symbols and re-export these JNI entrypoints from
+// Rust...
+//
+// https://github.com/rust-lang/rfcs/issues/2771
+extern "C" {
+    pub fn Java_com_google_androidgamesdk_GameActivity_loadNativeCode_C(
+        env: *mut JNIEnv,
+        javaGameActivity: jobject,
+        path: jstring,
+        funcName: jstring,
+        internalDataDir: jstring,
+        obbDir: jstring,
+        externalDataDir: jstring,
+        jAssetMgr: jobject,
+        savedState: jbyteArray,
+    ) -> jlong;
+
+    pub fn GameActivity_onCreate_C(
+        activity: *mut GameActivity,
+        savedState: *mut ::std::os::raw::c_void,
+        savedStateSize: libc::size_t,
+    );
+}
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_google_androidgamesdk_GameActivity_loadNativeCode(
+    env: *mut JNIEnv,
+    java_game_activity: jobject,
+    path: jstring,
+    func_name: jstring,
+    internal_data_dir: jstring,
+    obb_dir: jstring,
+    external_data_dir: jstring,
+    jasset_mgr: jobject,
+    saved_state: jbyteArray,
+) -> jlong {
+    Java_com_google_androidgamesdk_GameActivity_loadNativeCode_C(
+        env,
+        java_game_activity,
+        path,
+        func_name,
+        internal_data_dir,
+        obb_dir,
+        external_data_dir,
+        jasset_mgr,
+        saved_state,
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn GameActivity_onCreate(
+    activity: *mut GameActivity,
+    saved_state: *mut std::os::raw::c_void,
+    saved_state_size: libc::size_t,
+) {
+    GameActivity_onCreate_C(activity, saved_state, saved_state_size);
+}
@@ -0,0 +1,43 @@
+use ndk_sys::*;
+use std::ffi::{CStr, CString};
+
+/// Names of the files directly inside an asset directory.
+pub unsafe fn list(manager: *mut AAssetManager, dir: &str) -> Vec<String> {
+    let mut names = vec![];
+    let dir = CString::new(dir).unwrap();
+
+    let asset_dir = AAssetManager_openDir(manager, dir.as_ptr());
+    if asset_dir.is_null() {
+        return names;
+    }
+    loop {
+        let name = AAssetDir_getNextFileName(asset_dir);
+        if name.is_null() {
+            break;
+        }
+        names.push(CStr::from_ptr(name).to_string_lossy().into_owned());
+    }
+    AAssetDir_close(asset_dir);
+
+    names.sort();
+    names
+}
+
+pub unsafe fn read(manager: *mut AAssetManager, path: &str) -> Option<Vec<u8>> {
+    let path = CString::new(path).ok()?;
+
+    let asset = AAssetManager_open(manager, path.as_ptr(), AASSET_MODE_BUFFER as i32);
+    if asset.is_null() {
+        return None;
+    }
+    let buffer = AAsset_getBuffer(asset) as *const u8;
+    let data = if buffer.is_null() {
+        None
+    } else {
+        let len = AAsset_getLength(asset) as usize;
+        Some(std::slice::from_raw_parts(buffer, len).to_vec())
+    };
+    AAsset_close(asset);
+
+    data
+}
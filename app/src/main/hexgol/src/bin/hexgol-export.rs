@@ -0,0 +1,195 @@
+//! Draws a pattern file on an empty board and saves it as a PNG or SVG, or
+//! records it running as a GIF or APNG.
+//!
+//! ```text
+//! hexgol-export PATTERN OUTPUT [--theme NAME|FILE] [--board-size N] [--cell-size PIXELS]
+//!     [--no-grid] [--gpu] [--generations N] [--fps N] [--crop X,Y,WIDTH,HEIGHT]
+//! ```
+//!
+//! The format follows the extension of `OUTPUT`. Recordings run the pattern's
+//! own rule if it names one. With `--gpu`, a PNG is a screenshot taken with
+//! the app's renderer, without a window, rather than drawn on the CPU.
+
+use hexgol::export::*;
+use hexgol::game::{HexGOL, Pattern};
+use hexgol::renderer::{screenshot, Graphics};
+use hexgol::settings::BOARD_SIZES;
+use hexgol::theme::Theme;
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+const USAGE: &str = "usage: hexgol-export PATTERN OUTPUT [--theme NAME|FILE] [--board-size N] \
+[--cell-size PIXELS] [--no-grid] [--gpu] [--generations N] [--fps N] [--crop X,Y,WIDTH,HEIGHT]";
+/// The board size the app starts with.
+const DEFAULT_BOARD_SIZE: i32 = 35;
+/// The biggest board the app offers.
+const MAX_BOARD_SIZE: i32 = BOARD_SIZES[BOARD_SIZES.len() - 1];
+const DEFAULT_GENERATIONS: u32 = 60;
+const DEFAULT_FPS: u32 = 10;
+/// Multisampling for `--gpu`, as in the app.
+const SAMPLE_COUNT: u32 = 4;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut paths = vec![];
+    let mut theme = Theme::default();
+    let mut board_size = DEFAULT_BOARD_SIZE;
+    let mut options = ExportOptions::default();
+    let mut generations = DEFAULT_GENERATIONS;
+    let mut fps = DEFAULT_FPS;
+    let mut crop = None;
+    let mut gpu = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--theme" => theme = load_theme(&value()?)?,
+            "--board-size" => board_size = positive(&arg, &value()?)?,
+            "--cell-size" => options.cell_size = positive(&arg, &value()?)?,
+            "--no-grid" => options.grid = false,
+            "--gpu" => gpu = true,
+            "--generations" => generations = number(&arg, &value()?)?,
+            "--fps" => fps = positive(&arg, &value()?)?,
+            "--crop" => crop = Some(parse_crop(&value()?)?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg),
+        }
+    }
+    let (pattern_path, output) = match paths.as_slice() {
+        [pattern, output] => (pattern, Path::new(output)),
+        _ => return Err(USAGE.to_string()),
+    };
+    if board_size > MAX_BOARD_SIZE {
+        return Err(format!("--board-size can be at most {}", MAX_BOARD_SIZE));
+    }
+
+    let text = std::fs::read_to_string(pattern_path)
+        .map_err(|err| format!("{}: {}", pattern_path, err))?;
+    let pattern = Pattern::parse(&text).map_err(|err| format!("{}: {}", pattern_path, err))?;
+    let mut game = HexGOL::new(board_size);
+    for hex in &pattern.cells {
+        game.set(hex, true);
+    }
+    if let Some(rule) = pattern.rule {
+        game.set_rule(rule);
+    }
+
+    let extension = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    if gpu && extension != "png" {
+        return Err("--gpu only takes PNG screenshots".to_string());
+    }
+    let bytes = match extension {
+        "png" => {
+            fits(image_size(board_size, &options), PNG_MAX_SIZE, "PNG")?;
+            let image = match gpu {
+                true => take_screenshot(&game, &theme, &options)?,
+                false => rasterize(&game, &theme, &options),
+            };
+            encode_png(&image)
+        }
+        "svg" => to_svg(&game, &theme, &options).into_bytes(),
+        _ => {
+            let format = AnimationFormat::from_extension(extension)
+                .ok_or("OUTPUT should end in .png, .svg, .gif or .apng")?;
+            let crop = crop.unwrap_or_else(|| Crop::full(board_size, &options));
+            fits([crop.width, crop.height], format.max_size(), format.name())?;
+            if fps > format.max_fps() {
+                return Err(format!(
+                    "{} plays at most {} frames a second",
+                    format.name(),
+                    format.max_fps()
+                ));
+            }
+            record(&mut game, &theme, &options, crop, format, fps, generations)
+        }
+    };
+    std::fs::write(output, bytes).map_err(|err| format!("{}: {}", output.display(), err))
+}
+
+/// Draws the board with the app's renderer on whatever adapter there is, down
+/// to a software one.
+fn take_screenshot(game: &HexGOL, theme: &Theme, options: &ExportOptions) -> Result<Image, String> {
+    // `screenshot` resizes the frame once it's known to fit.
+    let mut gfx = pollster::block_on(Graphics::headless([1, 1], SAMPLE_COUNT))
+        .map_err(|err| format!("can't take a screenshot: {}", err))?;
+    let max = gfx.context().device.limits().max_texture_dimension_2d;
+    fits(
+        image_size(game.size(), options),
+        max,
+        "the graphics adapter",
+    )?;
+    screenshot(&mut gfx, game, theme, options)
+        .ok_or("couldn't read the screenshot back".to_string())
+}
+
+/// Checks the picture isn't too big for the file format.
+fn fits(size: [u32; 2], max: u32, format: &str) -> Result<(), String> {
+    if size[0] > max || size[1] > max {
+        return Err(format!(
+            "a {}x{} picture is too big for {}, which goes up to {} pixels a side",
+            size[0], size[1], format, max
+        ));
+    }
+    Ok(())
+}
+
+/// A built-in theme by name, or else a theme config file.
+fn load_theme(name: &str) -> Result<Theme, String> {
+    if let Some(theme) = Theme::by_name(name) {
+        return Ok(theme);
+    }
+    let text = std::fs::read_to_string(name).map_err(|err| format!("{}: {}", name, err))?;
+    Theme::parse(&text).map_err(|err| format!("{}: {}", name, err))
+}
+
+fn number<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} takes a number, not {}", option, value))
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(option: &str, value: &str) -> Result<T, String> {
+    Some(number(option, value)?)
+        .filter(|n| *n > T::default())
+        .ok_or(format!("{} has to be more than 0", option))
+}
+
+/// `X,Y,WIDTH,HEIGHT` in pixels of the whole picture.
+fn parse_crop(value: &str) -> Result<Crop, String> {
+    let parts = value
+        .split(',')
+        .map(|part| number("--crop", part.trim()))
+        .collect::<Result<Vec<u32>, _>>()?;
+    match parts.as_slice() {
+        [x, y, width, height] if *width > 0 && *height > 0 => {
+            if x.checked_add(*width).is_none() || y.checked_add(*height).is_none() {
+                return Err(format!("--crop runs past {} pixels", u32::MAX));
+            }
+            Ok(Crop {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            })
+        }
+        _ => Err("--crop takes X,Y,WIDTH,HEIGHT with a width and height over 0".to_string()),
+    }
+}
@@ -0,0 +1,95 @@
+struct CameraTransform {
+    scale: vec2<f32>,
+    offset: vec2<f32>,
+}
+
+struct Board {
+    live: vec4<f32>,
+    dying: vec4<f32>,
+    // Radius of the board in cells.
+    size: i32,
+    // Cells per row of the bitmap, 2 * size + 1.
+    width: i32,
+    // Fraction of the tick since the last generation.
+    progress: f32,
+    // Whether births and deaths are animated.
+    animate: u32,
+}
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+};
+
+struct Cell {
+    // Axial coordinates.
+    @location(1) hex: vec2<i32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraTransform;
+
+@group(1) @binding(0)
+var<uniform> board: Board;
+// The cell bitmaps are bound by board_buffers.wgsl or board_textures.wgsl,
+// put before this file, which define cell_word and previous_word to read them:
+// one bit per cell of the (2 * size + 1)² square of axial coordinates, for
+// this generation and the previous one.
+
+fn bit(word: u32, index: u32) -> bool {
+    return ((word >> (index % 32u)) & 1u) == 1u;
+}
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+    cell: Cell,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let index = u32((cell.hex.y + board.size) * board.width + cell.hex.x + board.size);
+    let alive = bit(cell_word(index / 32u), index);
+    let was_alive = bit(previous_word(index / 32u), index);
+
+    if (alive) {
+        out.color = board.live;
+    } else {
+        out.color = board.dying;
+    }
+
+    // Cells that just died only show while their death is animated.
+    if (!alive && (!was_alive || board.animate == 0u)) {
+        // Every vertex on the same point leaves nothing to rasterise.
+        out.clip_position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+
+    // Born cells grow and fade in, dead ones shrink while taking on the dying
+    // colour.
+    var scale = 1.0;
+    if (board.animate != 0u) {
+        let t = clamp(board.progress, 0.0, 1.0);
+        if (alive && !was_alive) {
+            scale = t;
+            out.color = vec4<f32>(board.live.rgb, t);
+        } else if (!alive) {
+            scale = 1.0 - t;
+            out.color = mix(board.live, board.dying, t);
+        }
+    }
+
+    let q = f32(cell.hex.x);
+    let r = f32(cell.hex.y);
+    let center = vec2<f32>(1.5 * q, sqrt(3.0) / 2.0 * q + sqrt(3.0) * r);
+    out.clip_position = vec4<f32>((in.position * scale + center - camera.offset) * camera.scale, 1.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
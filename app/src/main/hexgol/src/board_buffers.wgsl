@@ -0,0 +1,14 @@
+// The board's bitmaps in storage buffers, which GpuLife steps.
+
+@group(1) @binding(1)
+var<storage, read> cells: array<u32>;
+@group(1) @binding(2)
+var<storage, read> previous: array<u32>;
+
+fn cell_word(word: u32) -> u32 {
+    return cells[word];
+}
+
+fn previous_word(word: u32) -> u32 {
+    return previous[word];
+}
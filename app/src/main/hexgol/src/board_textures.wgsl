@@ -0,0 +1,19 @@
+// The board's bitmaps in textures, for GLES 3.0 where vertex shaders can't
+// read storage buffers. Each texel is one word, 64 to a row.
+
+@group(1) @binding(1)
+var cells: texture_2d<u32>;
+@group(1) @binding(2)
+var previous: texture_2d<u32>;
+
+fn texel(word: u32) -> vec2<i32> {
+    return vec2<i32>(i32(word % 64u), i32(word / 64u));
+}
+
+fn cell_word(word: u32) -> u32 {
+    return textureLoad(cells, texel(word), 0).x;
+}
+
+fn previous_word(word: u32) -> u32 {
+    return textureLoad(previous, texel(word), 0).x;
+}
@@ -0,0 +1,410 @@
+use super::*;
+use crate::theme::Theme;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+impl AnimationFormat {
+    /// The format a file name's extension asks for.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "gif" => Some(Self::Gif),
+            "apng" => Some(Self::Apng),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gif => "GIF",
+            Self::Apng => "APNG",
+        }
+    }
+    /// The widest or tallest frame the format can hold. GIF stores sizes in
+    /// 16 bits.
+    pub fn max_size(&self) -> u32 {
+        match self {
+            Self::Gif => u16::MAX as u32,
+            Self::Apng => PNG_MAX_SIZE,
+        }
+    }
+    /// The fastest the format plays. GIF delays count hundredths of a second,
+    /// and viewers slow anything under two of them right down.
+    pub fn max_fps(&self) -> u32 {
+        match self {
+            Self::Gif => 50,
+            Self::Apng => u16::MAX as u32,
+        }
+    }
+}
+
+/// The rectangle of a frame that changed since the one before, as palette
+/// indices, which is all either format has to store of it.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Patch {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub indices: Vec<u8>,
+}
+impl Patch {
+    /// The whole of a frame.
+    fn full(size: [u32; 2], indices: &[u8]) -> Self {
+        Self::crop(size, indices, [0, 0], size)
+    }
+
+    /// The smallest rectangle of `next` that differs from `last`. Formats
+    /// need at least a pixel per frame, so a frame that didn't change keeps
+    /// its top left one.
+    fn between(size: [u32; 2], last: &[u8], next: &[u8]) -> Self {
+        let width = size[0] as usize;
+        let mut min = [u32::MAX; 2];
+        let mut max = [0; 2];
+        for (i, _) in last
+            .iter()
+            .zip(next)
+            .enumerate()
+            .filter(|(_i, (a, b))| a != b)
+        {
+            let at = [(i % width) as u32, (i / width) as u32];
+            for axis in 0..2 {
+                min[axis] = min[axis].min(at[axis]);
+                max[axis] = max[axis].max(at[axis]);
+            }
+        }
+        if min[0] == u32::MAX {
+            return Self::crop(size, next, [0, 0], [1, 1]);
+        }
+        Self::crop(size, next, min, [max[0] - min[0] + 1, max[1] - min[1] + 1])
+    }
+
+    fn crop(size: [u32; 2], indices: &[u8], at: [u32; 2], patch: [u32; 2]) -> Self {
+        let [width, height] = patch.map(|n| n as usize);
+        let mut cropped = Vec::with_capacity(width * height);
+        for y in at[1] as usize..at[1] as usize + height {
+            let row = y * size[0] as usize + at[0] as usize;
+            cropped.extend(&indices[row..row + width]);
+        }
+        Self {
+            x: at[0],
+            y: at[1],
+            width: patch[0],
+            height: patch[1],
+            indices: cropped,
+        }
+    }
+}
+
+/// An animation being recorded a frame at a time, in the colours of a theme.
+/// Only what changed from one frame to the next is kept, and encoded at the
+/// end.
+pub struct Animation {
+    format: AnimationFormat,
+    palette: Palette,
+    size: [u32; 2],
+    fps: u32,
+    /// Frames promised up front, which APNG needs to know.
+    frames: u32,
+    patches: Vec<Patch>,
+    /// Palette indices of the last frame.
+    last: Option<Vec<u8>>,
+}
+impl Animation {
+    /// Expects `frames` frames of `size` pixels, shown at `fps` a second up
+    /// to the format's `max_fps`. `size` can't be more than the format's
+    /// `max_size`, and there's always at least one frame.
+    pub fn new(
+        format: AnimationFormat,
+        theme: &Theme,
+        size: [u32; 2],
+        fps: u32,
+        frames: u32,
+    ) -> Self {
+        assert!(
+            size[0] <= format.max_size() && size[1] <= format.max_size(),
+            "{}x{} is too big for {}",
+            size[0],
+            size[1],
+            format.name()
+        );
+        Self {
+            format,
+            palette: Palette::for_theme(theme),
+            size,
+            fps: fps.clamp(1, format.max_fps()),
+            frames: frames.max(1),
+            patches: vec![],
+            last: None,
+        }
+    }
+
+    /// Adds a frame, which has to be the size the animation was made with.
+    /// Frames past the number promised are dropped.
+    pub fn push(&mut self, image: &Image) {
+        debug_assert_eq!([image.width, image.height], self.size);
+        if self.patches.len() < self.frames as usize {
+            let indices = self.palette.quantize(image);
+            self.patches.push(match &self.last {
+                Some(last) => Patch::between(self.size, last, &indices),
+                None => Patch::full(self.size, &indices),
+            });
+            self.last = Some(indices);
+        }
+    }
+
+    /// The encoded file. Frames still missing repeat the last one, or are
+    /// background if there were none.
+    pub fn finish(mut self) -> Vec<u8> {
+        let [width, height] = self.size;
+        let last = self
+            .last
+            .take()
+            .unwrap_or_else(|| vec![0; width as usize * height as usize]);
+        if self.patches.is_empty() {
+            self.patches.push(Patch::full(self.size, &last));
+        }
+        while self.patches.len() < self.frames as usize {
+            self.patches.push(Patch::between(self.size, &last, &last));
+        }
+
+        match self.format {
+            AnimationFormat::Gif => {
+                // GIF counts in hundredths of a second, so frames are rounded
+                // to keep the rate right on average.
+                let fps = self.fps as u64;
+                let at = |frame: u64| (frame * 100 + fps / 2) / fps;
+                let frames: Vec<(Patch, u16)> = (0..)
+                    .zip(self.patches)
+                    .map(|(i, patch)| (patch, (at(i + 1) - at(i)) as u16))
+                    .collect();
+                encode_gif(self.size, &self.palette, &frames)
+                    .expect("frames fit the GIF they're encoded into")
+            }
+            AnimationFormat::Apng => {
+                encode_apng(self.size, &self.palette, self.fps as u16, &self.patches)
+                    .expect("frames fit the APNG they're encoded into")
+            }
+        }
+    }
+}
+
+/// Records `generations` steps of `game`, starting with the board as it is,
+/// so there's one more frame than generations. Only `crop` of the picture
+/// `rasterize` would draw is kept.
+pub fn record(
+    game: &mut HexGOL,
+    theme: &Theme,
+    options: &ExportOptions,
+    crop: Crop,
+    format: AnimationFormat,
+    fps: u32,
+    generations: u32,
+) -> Vec<u8> {
+    let size = [crop.width, crop.height];
+    let mut animation = Animation::new(format, theme, size, fps, generations + 1);
+    animation.push(&rasterize_crop(game, theme, options, crop));
+    for _ in 0..generations {
+        game.update();
+        animation.push(&rasterize_crop(game, theme, options, crop));
+    }
+    animation.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Pattern;
+    use crate::theme::to_srgb8;
+
+    const OPTIONS: ExportOptions = ExportOptions {
+        cell_size: 4.0,
+        grid: true,
+    };
+    const GENERATIONS: u32 = 5;
+
+    fn pinwheel() -> HexGOL {
+        let pinwheel = Pattern::parse(include_str!("../../../assets/patterns/pinwheel.hexp"))
+            .expect("pattern parses");
+        let mut game = HexGOL::new(4);
+        game.set_rule(pinwheel.rule.expect("pattern has a rule"));
+        for hex in &pinwheel.cells {
+            game.set(hex, true);
+        }
+        game
+    }
+
+    /// Palette indices of each frame `record` should hold.
+    fn expected_frames(palette: &Palette) -> Vec<Vec<u8>> {
+        let mut game = pinwheel();
+        let mut frames = vec![palette.quantize(&rasterize(&game, &Theme::classic(), &OPTIONS))];
+        for _ in 0..GENERATIONS {
+            game.update();
+            frames.push(palette.quantize(&rasterize(&game, &Theme::classic(), &OPTIONS)));
+        }
+        frames
+    }
+
+    fn record_pinwheel(format: AnimationFormat) -> Vec<u8> {
+        let mut game = pinwheel();
+        let crop = Crop::full(game.size(), &OPTIONS);
+        record(
+            &mut game,
+            &Theme::classic(),
+            &OPTIONS,
+            crop,
+            format,
+            10,
+            GENERATIONS,
+        )
+    }
+
+    /// Draws `indices`, `width` wide, at `at` on `canvas`.
+    fn paste(canvas: &mut [u8], size: [u32; 2], at: [u32; 2], width: u32, indices: &[u8]) {
+        for (row, line) in indices.chunks(width as usize).enumerate() {
+            let start = (at[1] as usize + row) * size[0] as usize + at[0] as usize;
+            canvas[start..start + line.len()].copy_from_slice(line);
+        }
+    }
+
+    #[test]
+    fn patches_hold_only_what_changed() {
+        let size = [4, 3];
+        let last = vec![0; 12];
+        let mut next = last.clone();
+        next[5] = 1;
+        next[10] = 2;
+        assert_eq!(
+            Patch::between(size, &last, &next),
+            Patch {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 2,
+                indices: vec![1, 0, 0, 2],
+            }
+        );
+        assert_eq!(
+            Patch::between(size, &last, &last),
+            Patch {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+                indices: vec![0],
+            }
+        );
+        assert_eq!(Patch::full(size, &next).indices, next);
+    }
+
+    #[test]
+    fn gif_plays_back_the_frames() {
+        let palette = Palette::for_theme(&Theme::classic());
+        let expected = expected_frames(&palette);
+        let gif = record_pinwheel(AnimationFormat::Gif);
+
+        let mut options = ::gif::DecodeOptions::new();
+        options.set_color_output(::gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(gif.as_slice()).expect("GIF decodes");
+        let size = [decoder.width() as u32, decoder.height() as u32];
+        let colors = decoder
+            .global_palette()
+            .expect("GIF has a palette")
+            .to_vec();
+        // GIF palettes hold a power of two colours, the theme's first.
+        assert!((colors.len() / 3).is_power_of_two());
+        assert!(colors.len() / 3 <= 256);
+        assert_eq!(colors[..palette.colors.len() * 3], palette.colors.concat());
+
+        let mut canvas = vec![0; size[0] as usize * size[1] as usize];
+        let mut frames = vec![];
+        while let Some(frame) = decoder.read_next_frame().expect("frame decodes") {
+            let at = [frame.left as u32, frame.top as u32];
+            paste(&mut canvas, size, at, frame.width as u32, &frame.buffer);
+            frames.push(canvas.clone());
+        }
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn apng_plays_back_the_frames() {
+        let palette = Palette::for_theme(&Theme::classic());
+        let expected = expected_frames(&palette);
+        let apng = record_pinwheel(AnimationFormat::Apng);
+
+        let mut decoder = ::png::Decoder::new(apng.as_slice());
+        decoder.set_transformations(::png::Transformations::IDENTITY);
+        let mut reader = decoder.read_info().expect("APNG decodes");
+        let info = reader.info();
+        let size = [info.width, info.height];
+        assert_eq!(
+            info.animation_control.expect("APNG is animated").num_frames,
+            expected.len() as u32
+        );
+        assert_eq!(info.palette.as_deref(), Some(&palette.colors.concat()[..]));
+
+        let mut canvas = vec![0; size[0] as usize * size[1] as usize];
+        let mut frames = vec![];
+        let mut buf = vec![0; reader.output_buffer_size()];
+        for _ in 0..expected.len() {
+            reader.next_frame(&mut buf).expect("frame decodes");
+            let control = reader.info().frame_control.expect("frame has a control");
+            let at = [control.x_offset, control.y_offset];
+            let len = control.width as usize * control.height as usize;
+            paste(&mut canvas, size, at, control.width, &buf[..len]);
+            frames.push(canvas.clone());
+        }
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn pads_missing_frames() {
+        let theme = Theme::classic();
+        let mut animation = Animation::new(AnimationFormat::Apng, &theme, [3, 2], 10, 4);
+        animation.push(&Image {
+            width: 3,
+            height: 2,
+            pixels: vec![to_srgb8(theme.live); 6],
+        });
+        let apng = animation.finish();
+        let reader = ::png::Decoder::new(apng.as_slice())
+            .read_info()
+            .expect("APNG decodes");
+        assert_eq!(reader.info().animation_control.unwrap().num_frames, 4);
+    }
+
+    #[test]
+    fn gif_frames_last_at_least_two_hundredths() {
+        for fps in [10, 30, 50, 60, 1000] {
+            let mut animation =
+                Animation::new(AnimationFormat::Gif, &Theme::classic(), [2, 2], fps, 7);
+            for _ in 0..7 {
+                animation.push(&Image {
+                    width: 2,
+                    height: 2,
+                    pixels: vec![[0; 3]; 4],
+                });
+            }
+            let gif = animation.finish();
+            let mut decoder = ::gif::DecodeOptions::new()
+                .read_info(gif.as_slice())
+                .expect("GIF decodes");
+            let mut delays = vec![];
+            while let Some(frame) = decoder.read_next_frame().expect("frame decodes") {
+                delays.push(frame.delay);
+            }
+            assert_eq!(delays.len(), 7);
+            assert!(
+                delays.iter().all(|delay| *delay >= 2),
+                "{:?} at {} fps",
+                delays,
+                fps
+            );
+            // Frames keep the rate on average.
+            let total: u16 = delays.iter().sum();
+            assert_eq!(total as u32, (700 + fps.min(50) / 2) / fps.min(50));
+        }
+    }
+}
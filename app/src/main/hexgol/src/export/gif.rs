@@ -0,0 +1,33 @@
+use super::*;
+
+/// Encodes the frames of an animation as a GIF that loops forever, each shown
+/// for its own number of hundredths of a second.
+pub(super) fn encode_gif(
+    size: [u32; 2],
+    palette: &Palette,
+    frames: &[(Patch, u16)],
+) -> Result<Vec<u8>, ::gif::EncodingError> {
+    let mut out = vec![];
+    let mut encoder = ::gif::Encoder::new(
+        &mut out,
+        size[0] as u16,
+        size[1] as u16,
+        &palette.colors.concat(),
+    )?;
+    encoder.set_repeat(::gif::Repeat::Infinite)?;
+    for (patch, delay) in frames {
+        encoder.write_frame(&::gif::Frame {
+            delay: *delay,
+            // Each frame only covers what changed, over the ones before.
+            dispose: ::gif::DisposalMethod::Keep,
+            left: patch.x as u16,
+            top: patch.y as u16,
+            width: patch.width as u16,
+            height: patch.height as u16,
+            buffer: patch.indices.as_slice().into(),
+            ..Default::default()
+        })?;
+    }
+    drop(encoder);
+    Ok(out)
+}
@@ -0,0 +1,84 @@
+//! Pictures of the board drawn on the CPU, so they don't need a GPU or a
+//! window.
+
+mod animation;
+pub use animation::*;
+mod gif;
+use gif::*;
+mod palette;
+pub use palette::*;
+mod png;
+pub use png::*;
+mod raster;
+pub use raster::*;
+mod svg;
+pub use svg::*;
+
+use crate::game::{border_edges, HexFract, HexGOL, HexInt};
+
+/// Space around the board, in cells.
+const MARGIN: f32 = 1.0;
+/// Width of grid and border lines, in pixels.
+const LINE_WIDTH: f32 = 1.0;
+
+/// How a board is drawn when exported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// Pixels from the centre of a cell to its corners.
+    pub cell_size: f32,
+    /// Whether every cell is outlined, as with the grid shown in the app.
+    pub grid: bool,
+}
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 8.0,
+            grid: true,
+        }
+    }
+}
+
+/// Size in pixels of a picture of a board with the given radius.
+pub fn image_size(board_size: i32, options: &ExportOptions) -> [u32; 2] {
+    half_extent(board_size).map(|half| (2.0 * half * options.cell_size).ceil() as u32)
+}
+
+/// Half the width and height of the board in world units, margin included.
+fn half_extent(board_size: i32) -> [f32; 2] {
+    let size = board_size as f32;
+    [
+        1.5 * size + 1.0 + MARGIN,
+        3.0f32.sqrt() * (size + 0.5) + MARGIN,
+    ]
+}
+
+/// Converts world space to pixels, with y growing down from the top left.
+fn to_pixels(world: [f32; 2], board_size: i32, options: &ExportOptions) -> [f32; 2] {
+    let half = half_extent(board_size);
+    [
+        (world[0] + half[0]) * options.cell_size,
+        (half[1] - world[1]) * options.cell_size,
+    ]
+}
+
+/// Corner `k` of a cell, counting counter-clockwise from the right.
+fn corner(center: [f32; 2], k: i32) -> [f32; 2] {
+    let theta = k as f32 * std::f32::consts::FRAC_PI_3;
+    [center[0] + theta.cos(), center[1] + theta.sin()]
+}
+
+fn center(hex: HexInt) -> [f32; 2] {
+    HexFract::from(hex).transform(1.0)
+}
+
+/// The live cells in a fixed order, so the same board always exports the
+/// same way.
+fn live_cells(game: &HexGOL) -> Vec<HexInt> {
+    let mut cells: Vec<_> = game
+        .iter()
+        .filter(|(_hex, cell)| **cell)
+        .map(|(hex, _cell)| *hex)
+        .collect();
+    cells.sort_by_key(|hex| (hex.r(), hex.q()));
+    cells
+}
@@ -0,0 +1,66 @@
+use super::*;
+use crate::theme::{to_srgb8, Theme};
+use std::collections::HashMap;
+
+/// Steps from one theme colour to another, for the edges smoothed between
+/// them.
+const BLEND_STEPS: u32 = 16;
+
+/// The colours of a theme a board is drawn with, and blends between each pair
+/// of them for smoothed edges. Well under 256 colours, so frames can be
+/// indexed without dithering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub colors: Vec<[u8; 3]>,
+}
+impl Palette {
+    pub fn for_theme(theme: &Theme) -> Self {
+        let base = [theme.background, theme.grid, theme.live, theme.border];
+        let mut colors = vec![];
+        let mut add = |color: [f32; 3]| {
+            let color = to_srgb8(color);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        };
+        for (i, a) in base.iter().enumerate() {
+            add(*a);
+            for b in &base[i + 1..] {
+                // Blended in linear space, as `rasterize` does.
+                for step in 1..BLEND_STEPS {
+                    let t = step as f32 / BLEND_STEPS as f32;
+                    add([0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t));
+                }
+            }
+        }
+        Self { colors }
+    }
+
+    /// The index of the nearest colour of the palette for each pixel.
+    pub fn quantize(&self, image: &Image) -> Vec<u8> {
+        // Frames have few distinct colours, so each is only looked up once.
+        let mut nearest = HashMap::new();
+        image
+            .pixels
+            .iter()
+            .map(|pixel| {
+                *nearest
+                    .entry(*pixel)
+                    .or_insert_with(|| self.nearest(*pixel))
+            })
+            .collect()
+    }
+
+    fn nearest(&self, color: [u8; 3]) -> u8 {
+        let distance = |other: &[u8; 3]| {
+            (0..3)
+                .map(|c| (color[c] as i32 - other[c] as i32).pow(2))
+                .sum::<i32>()
+        };
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_i, other)| distance(other))
+            .map_or(0, |(i, _other)| i as u8)
+    }
+}
@@ -0,0 +1,184 @@
+use super::*;
+
+/// The widest or tallest image a PNG can hold.
+pub const PNG_MAX_SIZE: u32 = i32::MAX as u32;
+
+/// Encodes an image as an 8-bit RGB PNG. The image can't be empty.
+pub fn encode_png(image: &Image) -> Vec<u8> {
+    let mut png = vec![];
+    let mut encoder = ::png::Encoder::new(&mut png, image.width, image.height);
+    encoder.set_color(::png::ColorType::Rgb);
+    encoder.set_depth(::png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .expect("an image with pixels encodes into memory");
+    writer
+        .write_image_data(&image.pixels.concat())
+        .expect("an image with pixels encodes into memory");
+    writer
+        .finish()
+        .expect("an image with pixels encodes into memory");
+    png
+}
+
+/// Encodes the frames of an animation as an indexed APNG that loops forever,
+/// showing each for `1 / fps` seconds.
+pub(super) fn encode_apng(
+    size: [u32; 2],
+    palette: &Palette,
+    fps: u16,
+    frames: &[Patch],
+) -> Result<Vec<u8>, ::png::EncodingError> {
+    let mut out = vec![];
+    let mut encoder = ::png::Encoder::new(&mut out, size[0], size[1]);
+    encoder.set_color(::png::ColorType::Indexed);
+    encoder.set_depth(::png::BitDepth::Eight);
+    encoder.set_palette(palette.colors.concat());
+    // The fast default compresses indexed frames poorly, several times larger.
+    encoder.set_compression(::png::Compression::Best);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    encoder.set_frame_delay(1, fps)?;
+    // Each frame only covers what changed, over the ones before.
+    encoder.set_dispose_op(::png::DisposeOp::None)?;
+    encoder.set_blend_op(::png::BlendOp::Source)?;
+
+    let mut writer = encoder.write_header()?;
+    for patch in frames {
+        // Moved to the corner first, so the new size can't reach past the edge.
+        writer.reset_frame_position()?;
+        writer.set_frame_dimension(patch.width, patch.height)?;
+        writer.set_frame_position(patch.x, patch.y)?;
+        writer.write_image_data(&patch.indices)?;
+    }
+    writer.finish()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in bytes {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    crc >> 1 ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    /// The type and data of each chunk, checking each one's CRC.
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + len]), "CRC of {:?}", kind);
+            chunks.push((kind.try_into().unwrap(), data));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    fn gradient(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: (0..width * height)
+                .map(|i| [(i % width) as u8, (i / width) as u8, (i * 7) as u8])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn writes_chunks_in_order_with_their_crcs() {
+        let png = encode_png(&gradient(40, 30));
+        let kinds: Vec<[u8; 4]> = chunks(&png).iter().map(|(kind, _data)| *kind).collect();
+        assert_eq!(kinds.first(), Some(b"IHDR"));
+        assert_eq!(kinds.last(), Some(b"IEND"));
+        assert!(kinds.contains(b"IDAT"));
+        // Image data comes in one run of chunks.
+        let idat: Vec<usize> = (0..kinds.len()).filter(|i| kinds[*i] == *b"IDAT").collect();
+        assert_eq!(idat.last().unwrap() - idat[0] + 1, idat.len());
+
+        let (_kind, header) = chunks(&png)[0];
+        assert_eq!(header[..8], [0, 0, 0, 40, 0, 0, 0, 30]);
+        // 8-bit RGB.
+        assert_eq!(header[8..10], [8, 2]);
+    }
+
+    #[test]
+    fn decodes_back() {
+        for image in [gradient(1, 1), gradient(40, 30), gradient(300, 2)] {
+            let png = encode_png(&image);
+            let mut reader = ::png::Decoder::new(png.as_slice()).read_info().unwrap();
+            let mut buf = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut buf).unwrap();
+            assert_eq!([info.width, info.height], [image.width, image.height]);
+            assert_eq!(buf[..info.buffer_size()], image.pixels.concat());
+        }
+    }
+
+    #[test]
+    fn writes_animation_chunks_in_order_with_their_crcs() {
+        let palette = Palette::for_theme(&crate::theme::Theme::classic());
+        let full = Patch {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 10,
+            indices: (0..200).map(|i| (i % 3) as u8).collect(),
+        };
+        let small = Patch {
+            x: 5,
+            y: 4,
+            width: 3,
+            height: 2,
+            indices: vec![1; 6],
+        };
+        let apng = encode_apng([20, 10], &palette, 10, &[full, small.clone(), small]).unwrap();
+        let chunks = chunks(&apng);
+        let kinds: Vec<[u8; 4]> = chunks.iter().map(|(kind, _data)| *kind).collect();
+        let first = |kind: &[u8; 4]| kinds.iter().position(|k| k == kind).unwrap();
+        assert_eq!(kinds.first(), Some(b"IHDR"));
+        assert_eq!(kinds.last(), Some(b"IEND"));
+        assert!(first(b"PLTE") < first(b"IDAT"));
+        assert!(first(b"acTL") < first(b"IDAT"));
+        // The first frame is the image itself, so its control comes before it.
+        assert!(first(b"fcTL") < first(b"IDAT"));
+        assert!(first(b"IDAT") < first(b"fdAT"));
+
+        let (_kind, actl) = chunks[first(b"acTL")];
+        assert_eq!(actl[..4], 3u32.to_be_bytes());
+        let controls: Vec<&[u8]> = chunks
+            .iter()
+            .filter(|(kind, _data)| kind == b"fcTL")
+            .map(|(_kind, data)| *data)
+            .collect();
+        assert_eq!(controls.len(), 3);
+        // Width, height, x and y of the second frame.
+        assert_eq!(
+            controls[1][4..20],
+            [0, 0, 0, 3, 0, 0, 0, 2, 0, 0, 0, 5, 0, 0, 0, 4]
+        );
+
+        // Frame controls and data share one run of sequence numbers.
+        let sequence: Vec<u32> = chunks
+            .iter()
+            .filter(|(kind, _data)| kind == b"fcTL" || kind == b"fdAT")
+            .map(|(_kind, data)| u32::from_be_bytes(data[..4].try_into().unwrap()))
+            .collect();
+        assert_eq!(sequence, (0..sequence.len() as u32).collect::<Vec<_>>());
+    }
+}
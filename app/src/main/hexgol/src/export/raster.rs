@@ -0,0 +1,157 @@
+use super::*;
+use crate::theme::{to_srgb8, Theme};
+
+/// Samples per pixel along each axis, to smooth the edges of cells.
+const SUPERSAMPLING: u32 = 3;
+/// Distance from the centre of a cell to the middle of its edges.
+const APOTHEM: f32 = 0.866_025_4;
+/// Directions of edges 0 to 2. Edges 3 to 5 face the other way.
+const EDGE_NORMALS: [[f32; 2]; 3] = [[APOTHEM, 0.5], [0.0, 1.0], [-APOTHEM, 0.5]];
+
+/// An opaque picture in 8-bit sRGB, row by row from the top.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+/// Part of a picture of the board, in pixels from its top left corner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+impl Crop {
+    /// The whole picture of the board.
+    pub fn full(board_size: i32, options: &ExportOptions) -> Self {
+        let [width, height] = image_size(board_size, options);
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+}
+
+/// Draws the board as the app does with the theme: live cells over the grid,
+/// if there is one, and the border of the board over both.
+pub fn rasterize(game: &HexGOL, theme: &Theme, options: &ExportOptions) -> Image {
+    rasterize_crop(game, theme, options, Crop::full(game.size(), options))
+}
+
+/// Draws part of what `rasterize` would. The crop may reach past the board,
+/// where there's only background.
+pub fn rasterize_crop(game: &HexGOL, theme: &Theme, options: &ExportOptions, crop: Crop) -> Image {
+    let Crop { width, height, .. } = crop;
+    let half = half_extent(game.size());
+    // Half a line, in world units.
+    let line = LINE_WIDTH / options.cell_size / 2.0;
+    let step = 1.0 / (SUPERSAMPLING as f32 * options.cell_size);
+    let samples = (SUPERSAMPLING * SUPERSAMPLING) as f32;
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let [x, y] = [x as u64 + crop.x as u64, y as u64 + crop.y as u64];
+            // Samples are averaged in linear space.
+            let mut sum = [0.0; 3];
+            for sy in 0..SUPERSAMPLING {
+                for sx in 0..SUPERSAMPLING {
+                    let world = [
+                        x as f32 / options.cell_size - half[0] + (sx as f32 + 0.5) * step,
+                        half[1] - y as f32 / options.cell_size - (sy as f32 + 0.5) * step,
+                    ];
+                    let color = paint(game, theme, options.grid, world, line);
+                    for (sum, c) in sum.iter_mut().zip(color) {
+                        *sum += c;
+                    }
+                }
+            }
+            pixels.push(to_srgb8(sum.map(|c| c / samples)));
+        }
+    }
+
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+/// The colour of the board at a point in world space. Lines reach `line`
+/// either side of the edges they follow.
+fn paint(game: &HexGOL, theme: &Theme, grid: bool, world: [f32; 2], line: f32) -> [f32; 3] {
+    let hex = HexInt::from(HexFract::inv_transform(&world, 1.0).round());
+    let pos = center(hex);
+    let offset = [world[0] - pos[0], world[1] - pos[1]];
+    let cell = game.get(&hex).copied();
+
+    // The nearest edge is the one the point is furthest towards.
+    let (nearest, normal) = EDGE_NORMALS
+        .iter()
+        .map(|n| {
+            let d = offset[0] * n[0] + offset[1] * n[1];
+            (d.abs(), n.map(|c| c * d.signum()))
+        })
+        .fold((0.0, [0.0; 2]), |a, b| if b.0 > a.0 { b } else { a });
+    let on_edge = APOTHEM - nearest < line;
+
+    if on_edge {
+        let neighbor = [
+            pos[0] + 2.0 * APOTHEM * normal[0],
+            pos[1] + 2.0 * APOTHEM * normal[1],
+        ];
+        let neighbor = HexInt::from(HexFract::inv_transform(&neighbor, 1.0).round());
+        if cell.is_some() != game.get(&neighbor).is_some() {
+            return theme.border;
+        }
+    }
+    match cell {
+        Some(true) => theme.live,
+        Some(false) if grid && on_edge => theme.grid,
+        _ => theme.background,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(image: &Image, color: [f32; 3]) -> usize {
+        let color = to_srgb8(color);
+        image.pixels.iter().filter(|pixel| **pixel == color).count()
+    }
+
+    #[test]
+    fn draws_a_known_board() {
+        // One live cell in the middle of a board of seven.
+        let mut game = HexGOL::new(1);
+        game.set(&HexInt::new(0, 0), true);
+        let theme = Theme::classic();
+        let options = ExportOptions {
+            cell_size: 10.0,
+            grid: false,
+        };
+        let image = rasterize(&game, &theme, &options);
+        assert_eq!([image.width, image.height], image_size(1, &options));
+        assert_eq!([image.width, image.height], [70, 72]);
+        let pixel = |x: u32, y: u32| image.pixels[(y * image.width + x) as usize];
+
+        assert_eq!(pixel(35, 36), to_srgb8(theme.live));
+        assert_eq!(pixel(0, 0), to_srgb8(theme.background));
+        // The middle of the cell to the right, which is dead.
+        assert_eq!(pixel(50, 27), to_srgb8(theme.background));
+        // A cell of radius 10 covers about 260 pixels, all but those smoothed
+        // along its edges wholly.
+        assert!((200..260).contains(&count(&image, theme.live)));
+        assert!(count(&image, theme.border) > 0);
+        assert_eq!(count(&image, theme.grid), 0);
+
+        let grid = rasterize(&game, &theme, &ExportOptions::default());
+        assert!(count(&grid, theme.grid) > 0);
+    }
+}
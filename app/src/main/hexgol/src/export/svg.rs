@@ -0,0 +1,161 @@
+use super::*;
+use crate::theme::{to_srgb8, Theme};
+use std::fmt::Write;
+
+/// The board as an SVG the same size as `rasterize` draws it, with one
+/// polygon per live cell.
+pub fn to_svg(game: &HexGOL, theme: &Theme, options: &ExportOptions) -> String {
+    let [width, height] = image_size(game.size(), options);
+    let point = |world: [f32; 2]| {
+        let [x, y] = to_pixels(world, game.size(), options);
+        format!("{:.2},{:.2}", x, y)
+    };
+    let hexagon = |hex: HexInt| {
+        let pos = center(hex);
+        (0..6)
+            .map(|k| point(corner(pos, k)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut svg = String::new();
+    // Writing to a string can't fail.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        color(theme.background)
+    );
+
+    if options.grid {
+        let _ = write!(
+            svg,
+            r#"<path fill="none" stroke="{}" stroke-width="{}" d=""#,
+            color(theme.grid),
+            LINE_WIDTH
+        );
+        for hex in all_cells(game) {
+            let _ = write!(svg, "M{}Z", hexagon(hex));
+        }
+        let _ = writeln!(svg, r#""/>"#);
+    }
+
+    let _ = writeln!(svg, r#"<g fill="{}">"#, color(theme.live));
+    for hex in live_cells(game) {
+        let _ = writeln!(svg, r#"<polygon points="{}"/>"#, hexagon(hex));
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = write!(
+        svg,
+        r#"<path fill="none" stroke="{}" stroke-width="{}" d=""#,
+        color(theme.border),
+        LINE_WIDTH
+    );
+    for [a, b] in border_edges(game.size()) {
+        let _ = write!(svg, "M{}L{}", point(a), point(b));
+    }
+    let _ = writeln!(svg, r#""/>"#);
+
+    svg += "</svg>\n";
+    svg
+}
+
+/// Every cell of the board, in the order `live_cells` uses.
+fn all_cells(game: &HexGOL) -> Vec<HexInt> {
+    let mut cells: Vec<_> = game.iter().map(|(hex, _cell)| *hex).collect();
+    cells.sort_by_key(|hex| (hex.r(), hex.q()));
+    cells
+}
+
+fn color(color: [f32; 3]) -> String {
+    let [r, g, b] = to_srgb8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> HexGOL {
+        let mut game = HexGOL::new(3);
+        for [q, r] in [[0, 0], [1, 0], [-2, 3], [3, -3]] {
+            game.set(&HexInt::new(q, r), true);
+        }
+        game
+    }
+
+    /// The value of each `attribute="..."` in the SVG, in order.
+    fn attributes<'a>(svg: &'a str, attribute: &str) -> Vec<&'a str> {
+        let key = format!(r#" {}=""#, attribute);
+        svg.match_indices(&key)
+            .map(|(i, _key)| {
+                let value = &svg[i + key.len()..];
+                &value[..value.find('"').unwrap()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draws_a_polygon_per_live_cell() {
+        let game = board();
+        let svg = to_svg(&game, &Theme::classic(), &ExportOptions::default());
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        let [width, height] = image_size(3, &ExportOptions::default());
+        assert_eq!(attributes(&svg, "width")[0], width.to_string());
+        assert_eq!(attributes(&svg, "height")[0], height.to_string());
+
+        let polygons = attributes(&svg, "points");
+        assert_eq!(svg.matches("<polygon").count(), 4);
+        assert_eq!(polygons.len(), 4);
+        for points in polygons {
+            assert_eq!(points.split(' ').count(), 6);
+        }
+    }
+
+    #[test]
+    fn uses_the_theme_colours() {
+        for theme in [Theme::classic(), Theme::light()] {
+            let svg = to_svg(&board(), &theme, &ExportOptions::default());
+            assert_eq!(
+                attributes(&svg, "fill"),
+                [
+                    color(theme.background).as_str(),
+                    "none",
+                    &color(theme.live),
+                    "none"
+                ]
+            );
+            assert_eq!(
+                attributes(&svg, "stroke"),
+                [color(theme.grid), color(theme.border)]
+            );
+        }
+    }
+
+    #[test]
+    fn grid_follows_the_options() {
+        let theme = Theme::classic();
+        let grid = color(theme.grid);
+        let with = to_svg(&board(), &theme, &ExportOptions::default());
+        // A hexagon outline for every cell of a board of radius 3.
+        let outlines = attributes(&with, "d")[0].matches('Z').count();
+        assert_eq!(outlines, 37);
+        assert!(attributes(&with, "stroke").contains(&grid.as_str()));
+
+        let options = ExportOptions {
+            grid: false,
+            ..ExportOptions::default()
+        };
+        let without = to_svg(&board(), &theme, &options);
+        assert_eq!(attributes(&without, "stroke"), [color(theme.border)]);
+        assert_eq!(attributes(&without, "d").len(), 1);
+        assert_eq!(without.matches("<polygon").count(), 4);
+    }
+}
@@ -0,0 +1,97 @@
+#![allow(nonstandard_style)]
+#![allow(dead_code)]
+
+// The generated bindings were made against a newer GameActivity that double
+// buffers input. The glue that build.rs actually compiles still stores events
+// inline in `android_app`, so these mirror
+// game-activity-csrc/game-activity/native_app_glue/android_native_app_glue.h.
+
+use super::{android_app, GameActivity};
+use ndk_sys::{AConfiguration, ALooper, ANativeWindow, ARect};
+use std::os::raw::{c_int, c_void};
+
+pub const GAME_ACTIVITY_POINTER_INFO_AXIS_COUNT: usize = 48;
+pub const GAMEACTIVITY_MAX_NUM_POINTERS_IN_MOTION_EVENT: usize = 8;
+pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: usize = 4;
+pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: usize = 4;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GluePointerAxes {
+    pub id: i32,
+    pub axisValues: [f32; GAME_ACTIVITY_POINTER_INFO_AXIS_COUNT],
+    pub rawX: f32,
+    pub rawY: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GlueMotionEvent {
+    pub deviceId: i32,
+    pub source: i32,
+    pub action: i32,
+    pub eventTime: i64,
+    pub downTime: i64,
+    pub flags: i32,
+    pub metaState: i32,
+    pub actionButton: i32,
+    pub buttonState: i32,
+    pub classification: i32,
+    pub edgeFlags: i32,
+    pub pointerCount: u32,
+    pub pointers: [GluePointerAxes; GAMEACTIVITY_MAX_NUM_POINTERS_IN_MOTION_EVENT],
+    pub precisionX: f32,
+    pub precisionY: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GlueKeyEvent {
+    pub deviceId: i32,
+    pub source: i32,
+    pub action: i32,
+    pub eventTime: i64,
+    pub downTime: i64,
+    pub flags: i32,
+    pub metaState: i32,
+    pub modifiers: i32,
+    pub repeatCount: i32,
+    pub keyCode: i32,
+}
+
+/// The public part of the vendored `android_app`.
+#[repr(C)]
+pub struct GlueApp {
+    pub userData: *mut c_void,
+    pub onAppCmd: Option<unsafe extern "C" fn(app: *mut android_app, cmd: i32)>,
+    pub activity: *mut GameActivity,
+    pub config: *mut AConfiguration,
+    pub savedState: *mut c_void,
+    pub savedStateSize: libc::size_t,
+    pub looper: *mut ALooper,
+    pub window: *mut ANativeWindow,
+    pub contentRect: ARect,
+    pub activityState: c_int,
+    pub destroyRequested: c_int,
+    pub motionEvents: [GlueMotionEvent; NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS],
+    pub motionEventsCount: u64,
+    pub keyUpEvents: [GlueKeyEvent; NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS],
+    pub keyUpEventsCount: u64,
+    pub keyDownEvents: [GlueKeyEvent; NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS],
+    pub keyDownEventsCount: u64,
+    pub textInputState: c_int,
+}
+
+#[allow(clashing_extern_declarations)]
+extern "C" {
+    #[link_name = "android_app_clear_motion_events"]
+    pub fn glue_clear_motion_events(app: *mut android_app);
+    #[link_name = "android_app_clear_key_up_events"]
+    pub fn glue_clear_key_up_events(app: *mut android_app);
+    #[link_name = "android_app_clear_key_down_events"]
+    pub fn glue_clear_key_down_events(app: *mut android_app);
+}
+
+pub unsafe fn glue(app: *mut android_app) -> *mut GlueApp {
+    app as *mut GlueApp
+}
@@ -0,0 +1,150 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+/// The live cells of the most recent generations, for heatmaps and trails.
+pub struct Activity {
+    history: VecDeque<(u64, Vec<HexInt>)>,
+    capacity: usize,
+}
+impl Activity {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records the current generation. Anything at or after it is dropped first,
+    /// like in `Stats`.
+    pub fn record(&mut self, game: &HexGOL) {
+        let generation = game.generation();
+        while let Some((last, _)) = self.history.back() {
+            if *last < generation {
+                break;
+            }
+            self.history.pop_back();
+        }
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+
+        let live = game
+            .iter()
+            .filter(|(_hex, cell)| **cell)
+            .map(|(hex, _cell)| *hex)
+            .collect();
+        self.history.push_back((generation, live));
+    }
+
+    /// How often each cell was alive over the generations recorded, from 0 to
+    /// 1. Cells that never were are left out.
+    pub fn heat(&self) -> HashMap<HexInt, f32> {
+        let mut counts: HashMap<HexInt, u32> = HashMap::new();
+        for (_generation, live) in &self.history {
+            for hex in live {
+                *counts.entry(*hex).or_insert(0) += 1;
+            }
+        }
+
+        // Out of what's been recorded, so the heat doesn't start dim while
+        // the window fills.
+        let window = self.history.len() as f32;
+        counts
+            .into_iter()
+            .map(|(hex, count)| (hex, count as f32 / window))
+            .collect()
+    }
+
+    /// Cells alive earlier in the window but dead now, fading from 1 just
+    /// after they died to 0 at the end of the window.
+    pub fn trails(&self) -> HashMap<HexInt, f32> {
+        let (latest, current) = match self.history.back() {
+            Some((generation, live)) => (*generation, live),
+            None => return HashMap::new(),
+        };
+
+        let mut trails = HashMap::new();
+        for (generation, live) in self.history.iter().rev().skip(1) {
+            let age = (latest - generation) as f32;
+            let fade = 1.0 - age / self.capacity as f32;
+            if fade <= 0.0 {
+                break;
+            }
+            for hex in live {
+                trails.entry(*hex).or_insert(fade);
+            }
+        }
+        for hex in current {
+            trails.remove(hex);
+        }
+        trails
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records `generations` generations of a board where one cell stays alive
+    /// and another blinks on every other generation.
+    fn blinking(capacity: usize, generations: u64) -> Activity {
+        let mut game = HexGOL::new(4);
+        let mut activity = Activity::new(capacity);
+        game.set(&HexInt::new(0, 0), true);
+        for generation in 0..generations {
+            game.set(&HexInt::new(2, 0), generation % 2 == 0);
+            game.set_generation(generation);
+            activity.record(&game);
+        }
+        activity
+    }
+
+    #[test]
+    fn heat_is_out_of_the_generations_recorded() {
+        let heat = blinking(64, 4).heat();
+        assert_eq!(heat[&HexInt::new(0, 0)], 1.0);
+        assert_eq!(heat[&HexInt::new(2, 0)], 0.5);
+        assert_eq!(heat.len(), 2);
+    }
+
+    #[test]
+    fn keeps_the_latest_generations() {
+        // Of the last three generations, 3 and 5 had the blinker off.
+        let heat = blinking(3, 6).heat();
+        assert_eq!(heat[&HexInt::new(2, 0)], 1.0 / 3.0);
+    }
+
+    #[test]
+    fn stepping_back_drops_later_generations() {
+        let mut game = HexGOL::new(4);
+        let mut activity = blinking(8, 4);
+        game.set_generation(1);
+        activity.record(&game);
+
+        // Generation 0 with the blinker and the empty board just recorded.
+        let heat = activity.heat();
+        assert_eq!(heat[&HexInt::new(0, 0)], 0.5);
+        assert_eq!(heat[&HexInt::new(2, 0)], 0.5);
+    }
+
+    #[test]
+    fn trails_fade_behind_dead_cells() {
+        let mut game = HexGOL::new(4);
+        let mut activity = Activity::new(4);
+        for (generation, q) in [0, 1, 2, 3].into_iter().enumerate() {
+            game.set(&HexInt::new(q, 0), true);
+            if q > 0 {
+                game.set(&HexInt::new(q - 1, 0), false);
+            }
+            game.set_generation(generation as u64);
+            activity.record(&game);
+        }
+
+        let trails = activity.trails();
+        assert_eq!(trails.get(&HexInt::new(3, 0)), None);
+        assert_eq!(trails[&HexInt::new(2, 0)], 0.75);
+        assert_eq!(trails[&HexInt::new(1, 0)], 0.5);
+        assert_eq!(trails[&HexInt::new(0, 0)], 0.25);
+        assert!(Activity::new(4).trails().is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// Longest period looked for when classifying an object.
+const MAX_PERIOD: u64 = 64;
+/// Objects that grow past this many cells are given up on.
+const MAX_POPULATION: usize = 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator {
+        period: u64,
+    },
+    Spaceship {
+        period: u64,
+        displacement: HexInt,
+    },
+    /// Dies, grows or doesn't repeat within `MAX_PERIOD` on its own.
+    Other,
+}
+impl ObjectKind {
+    /// Still lives are told apart by population, everything that moves or
+    /// oscillates by period, as apgsearch does.
+    fn prefix(&self, population: usize) -> String {
+        match self {
+            Self::StillLife => format!("xs{}", population),
+            Self::Oscillator { period } => format!("xp{}", period),
+            Self::Spaceship { period, .. } => format!("xq{}", period),
+            Self::Other => format!("ov{}", population),
+        }
+    }
+}
+impl std::fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StillLife => write!(f, "still life"),
+            Self::Oscillator { period } => write!(f, "oscillator p{}", period),
+            Self::Spaceship {
+                period,
+                displacement,
+            } => write!(
+                f,
+                "spaceship p{} ({:+},{:+})",
+                period,
+                displacement.q(),
+                displacement.r()
+            ),
+            Self::Other => write!(f, "other"),
+        }
+    }
+}
+
+pub struct CensusEntry {
+    /// Identifies the object regardless of phase, orientation and position.
+    pub code: String,
+    pub kind: ObjectKind,
+    pub population: usize,
+    pub count: usize,
+}
+
+/// The objects a board breaks down into, counted by type.
+pub struct Census {
+    pub generation: u64,
+    pub rule: Rule,
+    pub entries: Vec<CensusEntry>,
+}
+impl Census {
+    pub fn take(game: &HexGOL) -> Self {
+        let rule = game.rule();
+        let neighborhood = game.neighborhood();
+        let mut entries: HashMap<String, CensusEntry> = HashMap::new();
+
+        for cells in components(game) {
+            let (kind, canonical) = classify(&cells, rule, neighborhood);
+            let code = format!("{}_{}", kind.prefix(cells.len()), encode(&canonical));
+
+            entries
+                .entry(code.clone())
+                .or_insert(CensusEntry {
+                    code,
+                    kind,
+                    population: cells.len(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+
+        let mut entries: Vec<CensusEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.code.cmp(&b.code)));
+
+        Self {
+            generation: game.generation(),
+            rule,
+            entries,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "# Census at generation {}, rule {}\n# count\tcells\tkind\tcode\n",
+            self.generation, self.rule
+        );
+        for entry in &self.entries {
+            text += &format!(
+                "{}\t{}\t{}\t{}\n",
+                entry.count, entry.population, entry.kind, entry.code
+            );
+        }
+        text
+    }
+}
+
+/// Groups live cells connected through the game's neighbourhood. Cells two
+/// apart share a neighbour and so can interact, so they count as connected too.
+pub fn components(game: &HexGOL) -> Vec<Vec<HexInt>> {
+    let offsets = game.neighborhood().offsets();
+    let mut reach = vec![];
+    for a in offsets {
+        for b in offsets {
+            let hex = *a + *b;
+            if hex != HexInt::new(0, 0) && !reach.contains(&hex) {
+                reach.push(hex);
+            }
+        }
+    }
+
+    let mut unvisited: HashSet<HexInt> = game
+        .iter()
+        .filter(|(_hex, cell)| **cell)
+        .map(|(hex, _cell)| *hex)
+        .collect();
+    let mut components = vec![];
+
+    while let Some(&start) = unvisited.iter().next() {
+        unvisited.remove(&start);
+
+        let mut component = vec![start];
+        let mut i = 0;
+        while i < component.len() {
+            for offset in &reach {
+                let hex = component[i] + *offset;
+                if unvisited.remove(&hex) {
+                    component.push(hex);
+                }
+            }
+            i += 1;
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+/// Runs an object on its own until it repeats. Returns what it is and its
+/// canonical form, the smallest over every phase and symmetry.
+fn classify(cells: &[HexInt], rule: Rule, neighborhood: Neighborhood) -> (ObjectKind, Vec<HexInt>) {
+    let mut first = cells.to_vec();
+    let origin = normalize(&mut first);
+
+    // Births with no live neighbours would fill the infinite plane.
+    if rule.birth() & 1 == 1 {
+        return (ObjectKind::Other, canonical(&first));
+    }
+
+    let mut phases = vec![first.clone()];
+    let mut current: HashSet<HexInt> = cells.iter().copied().collect();
+    for period in 1..=MAX_PERIOD {
+        current = step(&current, rule, neighborhood);
+        if current.is_empty() || current.len() > MAX_POPULATION {
+            break;
+        }
+
+        let mut phase: Vec<HexInt> = current.iter().copied().collect();
+        let offset = normalize(&mut phase);
+        if phase == first {
+            let displacement = offset - origin;
+            let kind = if displacement != HexInt::new(0, 0) {
+                ObjectKind::Spaceship {
+                    period,
+                    displacement,
+                }
+            } else if period == 1 {
+                ObjectKind::StillLife
+            } else {
+                ObjectKind::Oscillator { period }
+            };
+            let canonical = phases.iter().map(|phase| canonical(phase)).min().unwrap();
+            return (kind, canonical);
+        }
+        phases.push(phase);
+    }
+
+    (ObjectKind::Other, canonical(&first))
+}
+
+/// One generation of an unbounded set of live cells.
+fn step(cells: &HashSet<HexInt>, rule: Rule, neighborhood: Neighborhood) -> HashSet<HexInt> {
+    let mut counts: HashMap<HexInt, i32> = HashMap::new();
+    for hex in cells {
+        counts.entry(*hex).or_insert(0);
+        for neighbor in neighborhood.offsets() {
+            *counts.entry(*hex + *neighbor).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(hex, count)| rule.next(cells.contains(hex), *count))
+        .map(|(hex, _count)| hex)
+        .collect()
+}
+
+/// Moves cells so the smallest q and r are zero and sorts them. Returns the
+/// offset that was removed.
+fn normalize(cells: &mut [HexInt]) -> HexInt {
+    let min_q = cells.iter().map(|hex| hex.q()).min().unwrap_or(0);
+    let min_r = cells.iter().map(|hex| hex.r()).min().unwrap_or(0);
+    let offset = HexInt::new(min_q, min_r);
+
+    for hex in cells.iter_mut() {
+        *hex = *hex - offset;
+    }
+    cells.sort();
+
+    offset
+}
+
+/// The smallest of the 12 orientations of a normalized object.
+fn canonical(cells: &[HexInt]) -> Vec<HexInt> {
+    let mut best: Option<Vec<HexInt>> = None;
+    for mirror in [false, true] {
+        for rotations in 0..6 {
+            let mut cells: Vec<HexInt> = cells
+                .iter()
+                .map(|hex| hex.transform(rotations, mirror))
+                .collect();
+            normalize(&mut cells);
+
+            if best.as_ref().is_none_or(|best| cells < *best) {
+                best = Some(cells);
+            }
+        }
+    }
+    best.unwrap_or_default()
+}
+
+/// Rows of a normalized object as hex digits, four columns per digit, joined by `z`.
+fn encode(cells: &[HexInt]) -> String {
+    let rows = cells.iter().map(|hex| hex.r()).max().map_or(0, |r| r + 1);
+    let columns = cells.iter().map(|hex| hex.q()).max().map_or(0, |q| q + 1);
+
+    let mut code = String::new();
+    for r in 0..rows {
+        if r > 0 {
+            code.push('z');
+        }
+        for chunk in 0..(columns + 3) / 4 {
+            let mut nibble = 0;
+            for bit in 0..4 {
+                if cells
+                    .binary_search(&HexInt::new(chunk * 4 + bit, r))
+                    .is_ok()
+                {
+                    nibble |= 1 << bit;
+                }
+            }
+            code.push(std::char::from_digit(nibble, 16).unwrap());
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PINWHEEL: &str = "O\nOO\n..O\nO\n";
+    /// Moves one step along q and r every three generations under B2/S2.
+    const SHIP: &str = include_str!("../../../assets/patterns/spaceship.hexp");
+
+    fn cells(text: &str) -> Vec<HexInt> {
+        Pattern::parse(text).expect("pattern parses").cells
+    }
+
+    fn kind(cells: &[HexInt], rule: &str) -> ObjectKind {
+        classify(cells, rule.parse().unwrap(), Neighborhood::Adjacent).0
+    }
+
+    #[test]
+    fn classifies_objects() {
+        // Each cell of a ring has two live neighbours, and no dead cell has three.
+        let ring: Vec<HexInt> = NEIGHBORS.to_vec();
+        assert_eq!(kind(&ring, "B3/S2"), ObjectKind::StillLife);
+        assert_eq!(
+            kind(&cells(PINWHEEL), "B2/S2"),
+            ObjectKind::Oscillator { period: 4 }
+        );
+        assert_eq!(
+            kind(&cells(SHIP), "B2/S2"),
+            ObjectKind::Spaceship {
+                period: 3,
+                displacement: HexInt::new(1, 1)
+            }
+        );
+        // A lone cell dies, and births from nothing never settle.
+        assert_eq!(kind(&[HexInt::new(0, 0)], "B2/S2"), ObjectKind::Other);
+        assert_eq!(kind(&ring, "B0/S2"), ObjectKind::Other);
+    }
+
+    #[test]
+    fn codes_ignore_phase_orientation_and_position() {
+        let rule: Rule = "B2/S2".parse().unwrap();
+        let (_, canonical) = classify(&cells(SHIP), rule, Neighborhood::Adjacent);
+        for rotations in 0..6 {
+            for mirror in [false, true] {
+                let mut moved: HashSet<HexInt> = cells(SHIP)
+                    .iter()
+                    .map(|hex| hex.transform(rotations, mirror) + HexInt::new(5, -3))
+                    .collect();
+                for _ in 0..rotations {
+                    moved = step(&moved, rule, Neighborhood::Adjacent);
+                }
+                let moved: Vec<HexInt> = moved.into_iter().collect();
+                let (kind, code) = classify(&moved, rule, Neighborhood::Adjacent);
+                assert!(matches!(kind, ObjectKind::Spaceship { period: 3, .. }));
+                assert_eq!(code, canonical);
+            }
+        }
+    }
+
+    #[test]
+    fn counts_a_board() {
+        let mut game = HexGOL::new(20);
+        let place = |game: &mut HexGOL, text: &str, at: HexInt, rotations: u8| {
+            for hex in cells(text) {
+                game.set(&(hex.transform(rotations, false) + at), true);
+            }
+        };
+        place(&mut game, PINWHEEL, HexInt::new(-10, 0), 0);
+        place(&mut game, PINWHEEL, HexInt::new(10, 0), 2);
+        place(&mut game, SHIP, HexInt::new(0, -10), 0);
+
+        let census = Census::take(&game);
+        let counts: Vec<(usize, ObjectKind)> = census
+            .entries
+            .iter()
+            .map(|entry| (entry.count, entry.kind))
+            .collect();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0], (2, ObjectKind::Oscillator { period: 4 }));
+        assert!(matches!(
+            counts[1],
+            (1, ObjectKind::Spaceship { period: 3, .. })
+        ));
+        assert_eq!(census.entries[0].population, 5);
+        assert!(census.entries[0].code.starts_with("xp4_"));
+        assert!(census.to_text().contains("oscillator p4"));
+    }
+}
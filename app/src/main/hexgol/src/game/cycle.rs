@@ -0,0 +1,181 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cycle {
+    /// Generations between repeats; 1 for a still life.
+    pub period: u64,
+    /// First generation of the repeating part.
+    pub start: u64,
+}
+
+/// A repeated hash, held until the board comes round again to show it wasn't
+/// a collision.
+struct Candidate {
+    cycle: Cycle,
+    /// The live cells when the hash repeated, in order.
+    cells: Vec<HexInt>,
+    /// The generation that should bring `cells` back.
+    due: u64,
+}
+
+/// Remembers the state hashes of recent generations to spot repeats.
+pub struct CycleDetector {
+    seen: HashMap<u64, u64>,
+    order: VecDeque<u64>,
+    window: usize,
+    candidate: Option<Candidate>,
+    cycle: Option<Cycle>,
+}
+impl CycleDetector {
+    pub fn new(window: usize) -> Self {
+        Self {
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            window,
+            candidate: None,
+            cycle: None,
+        }
+    }
+
+    /// Forgets everything and starts over from the given state.
+    pub fn reset(&mut self, hash: u64, generation: u64) {
+        self.seen.clear();
+        self.order.clear();
+        self.candidate = None;
+        self.cycle = None;
+        self.observe(hash, generation, Vec::new);
+    }
+
+    /// Records a generation, returning the cycle once it's certain. A repeated
+    /// hash is only reported after another period, if `cells` comes back the
+    /// same. `cells` lists the live cells in order, and is only called for
+    /// repeats.
+    pub fn observe(
+        &mut self,
+        hash: u64,
+        generation: u64,
+        cells: impl FnOnce() -> Vec<HexInt>,
+    ) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return None;
+        }
+
+        if let Some(candidate) = self.candidate.take_if(|c| c.due == generation) {
+            if cells() == candidate.cells {
+                self.cycle = Some(candidate.cycle);
+                return self.cycle;
+            }
+            // Two boards hashed the same; keep looking.
+            return self.record(hash, generation);
+        }
+
+        match self.seen.get(&hash) {
+            Some(&start) if self.candidate.is_none() => {
+                let period = generation - start;
+                self.candidate = Some(Candidate {
+                    cycle: Cycle { period, start },
+                    cells: cells(),
+                    due: generation + period,
+                });
+                None
+            }
+            Some(_) => None,
+            None => self.record(hash, generation),
+        }
+    }
+
+    fn record(&mut self, hash: u64, generation: u64) -> Option<Cycle> {
+        if self.seen.contains_key(&hash) {
+            return None;
+        }
+        self.seen.insert(hash, generation);
+        self.order.push_back(hash);
+        if self.order.len() > self.window {
+            if let Some(old) = self.order.pop_front() {
+                self.seen.remove(&old);
+            }
+        }
+        None
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+}
+
+/// Zobrist-style key of a live cell; a board hashes to the XOR of its live cells.
+pub fn cell_hash(hex: &HexInt) -> u64 {
+    // splitmix64
+    let mut x = ((hex.q() as u32 as u64) << 32) | hex.r() as u32 as u64;
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_period_of_an_oscillator() {
+        // The pinwheel, which turns over every 4 generations under B2/S2.
+        let mut game = HexGOL::new(10);
+        for (q, r) in [(-1, -1), (-1, 0), (0, 0), (1, 1), (-1, 2)] {
+            game.set(&HexInt::new(q, r), true);
+        }
+
+        let cycle = (0..20).find_map(|_| game.update());
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                period: 4,
+                start: 0
+            })
+        );
+        // Reported once the board came round a second time.
+        assert_eq!(game.generation(), 8);
+        assert_eq!(game.cycle(), cycle);
+    }
+
+    #[test]
+    fn edits_start_over() {
+        let mut game = HexGOL::new(6);
+        let cycle = (0..4).find_map(|_| game.update());
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                period: 1,
+                start: 0
+            })
+        );
+
+        game.set(&HexInt::new(0, 0), true);
+        game.set(&HexInt::new(1, 0), true);
+        assert_eq!(game.cycle(), None);
+    }
+
+    #[test]
+    fn collisions_are_not_cycles() {
+        let board = |n: i32| move || vec![HexInt::new(n, 0)];
+        let mut cycles = CycleDetector::new(16);
+        cycles.reset(1, 0);
+        assert_eq!(cycles.observe(2, 1, board(1)), None);
+        // Hash 1 again, but a different board.
+        assert_eq!(cycles.observe(1, 2, board(2)), None);
+        assert_eq!(cycles.observe(2, 3, board(3)), None);
+        assert_eq!(cycles.observe(3, 4, board(4)), None);
+        assert_eq!(cycles.cycle(), None);
+
+        // A real repeat is still found after a collision.
+        assert_eq!(cycles.observe(3, 5, board(5)), None);
+        assert_eq!(
+            cycles.observe(3, 6, board(5)),
+            Some(Cycle {
+                period: 1,
+                start: 4
+            })
+        );
+    }
+}
@@ -62,7 +62,7 @@ impl From<HexInt> for HexFract {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct HexInt {
     q: i32,
     r: i32,
@@ -80,6 +80,53 @@ impl HexInt {
     pub const fn s(&self) -> i32 {
         -self.q() - self.r()
     }
+
+    /// Rotates 60° counter-clockwise about the origin.
+    pub const fn rotate(&self) -> Self {
+        Self::new(-self.r(), -self.s())
+    }
+    /// Reflects across the horizontal axis.
+    pub const fn mirror(&self) -> Self {
+        Self::new(self.q(), self.s())
+    }
+    /// One of the 12 symmetries of the hex grid: `rotations` steps of 60°,
+    /// after an optional mirror.
+    pub const fn transform(&self, rotations: u8, mirror: bool) -> Self {
+        let mut hex = if mirror { self.mirror() } else { *self };
+        let mut i = 0;
+        while i < rotations % 6 {
+            hex = hex.rotate();
+            i += 1;
+        }
+        hex
+    }
+
+    pub fn distance(&self, other: &Self) -> i32 {
+        let d = *self - *other;
+        (d.q().abs() + d.r().abs() + d.s().abs()) / 2
+    }
+    /// Every hex on the straight line from `self` to `other`, both included.
+    pub fn line_to(&self, other: &Self) -> Vec<Self> {
+        let n = self.distance(other);
+        if n == 0 {
+            return vec![*self];
+        }
+
+        let a = HexFract::from(*self);
+        let b = HexFract::from(*other);
+        // Nudge off the edges between hexes so rounding is consistent.
+        let a = HexFract::new(a.q() + 1e-3, a.r() + 1e-3);
+        let b = HexFract::new(b.q() + 1e-3, b.r() + 1e-3);
+
+        (0..=n)
+            .map(|i| {
+                let t = i as f32 / n as f32;
+                HexInt::from(
+                    HexFract::new(a.q() + (b.q() - a.q()) * t, a.r() + (b.r() - a.r()) * t).round(),
+                )
+            })
+            .collect()
+    }
 }
 impl Add for HexInt {
     type Output = HexInt;
@@ -88,8 +135,126 @@ impl Add for HexInt {
         Self::new(self.q + rhs.q, self.r + rhs.r)
     }
 }
+impl Sub for HexInt {
+    type Output = HexInt;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
 impl From<HexFract> for HexInt {
     fn from(hex: HexFract) -> Self {
         Self::new(hex.q() as i32, hex.r() as i32)
     }
 }
+
+/// The edges around the outside of a board of radius `size`, as pairs of
+/// corners in world space.
+pub fn border_edges(size: i32) -> Vec<[[f32; 2]; 2]> {
+    let origin = HexInt::new(0, 0);
+    let mut edges = vec![];
+    for q in -size..=size {
+        for r in (-size).max(-q - size)..=size.min(-q + size) {
+            let pos = HexFract::from(HexInt::new(q, r)).transform(1.0);
+            let corner = |k: i32| {
+                let theta = k as f32 * std::f32::consts::FRAC_PI_3;
+                [pos[0] + theta.cos(), pos[1] + theta.sin()]
+            };
+            // Edge k runs between corners k and k + 1 and faces the
+            // neighbour at 30° + 60°k.
+            for k in 0..6 {
+                let theta = (k as f32 + 0.5) * std::f32::consts::FRAC_PI_3;
+                let neighbor = [
+                    pos[0] + 3.0f32.sqrt() * theta.cos(),
+                    pos[1] + 3.0f32.sqrt() * theta.sin(),
+                ];
+                let neighbor = HexInt::from(HexFract::inv_transform(&neighbor, 1.0).round());
+                if neighbor.distance(&origin) > size {
+                    edges.push([corner(k), corner(k + 1)]);
+                }
+            }
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(radius: i32) -> Vec<HexInt> {
+        let origin = HexInt::new(0, 0);
+        (-radius..=radius)
+            .flat_map(|q| (-radius..=radius).map(move |r| HexInt::new(q, r)))
+            .filter(|hex| hex.distance(&origin) == radius)
+            .collect()
+    }
+
+    #[test]
+    fn rotates_around_the_ring() {
+        let hex = HexInt::new(2, -1);
+        assert_eq!(hex.rotate().rotate().rotate(), HexInt::new(-2, 1));
+        assert_eq!(hex.transform(6, false), hex);
+        assert_eq!(hex.transform(8, false), hex.transform(2, false));
+
+        // Rotating a ring's hex visits six of them and keeps the distance.
+        let origin = HexInt::new(0, 0);
+        for hex in ring(3) {
+            let mut seen: Vec<HexInt> = (0..6).map(|i| hex.transform(i, false)).collect();
+            seen.sort();
+            seen.dedup();
+            assert_eq!(seen.len(), 6);
+            assert!(seen.iter().all(|h| h.distance(&origin) == 3));
+        }
+    }
+
+    #[test]
+    fn rotates_counter_clockwise_in_world_space() {
+        for hex in ring(2) {
+            let [x, y] = HexFract::from(hex).transform(1.0);
+            let [u, v] = HexFract::from(hex.rotate()).transform(1.0);
+            let (cos, sin) = (0.5, 3.0f32.sqrt() / 2.0);
+            assert!((x * cos - y * sin - u).abs() < 1e-4);
+            assert!((x * sin + y * cos - v).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn mirrors_across_the_horizontal_axis() {
+        for hex in ring(3) {
+            assert_eq!(hex.mirror().mirror(), hex);
+            let [x, y] = HexFract::from(hex).transform(1.0);
+            let [u, v] = HexFract::from(hex.mirror()).transform(1.0);
+            assert!((x - u).abs() < 1e-4 && (y + v).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn has_twelve_symmetries() {
+        let hex = HexInt::new(3, -1);
+        let mut images: Vec<HexInt> = (0..6)
+            .flat_map(|rotations| [false, true].map(|mirror| hex.transform(rotations, mirror)))
+            .collect();
+        images.sort();
+        images.dedup();
+        assert_eq!(images.len(), 12);
+    }
+
+    #[test]
+    fn finds_the_border() {
+        for size in 0..8 {
+            let edges = border_edges(size);
+            assert_eq!(edges.len() as i32, 6 * (2 * size + 1));
+
+            // Each edge is a unit long and lies further out than any cell's
+            // centre.
+            let outer = 1.5 * size as f32 + 0.5;
+            for [a, b] in edges {
+                let length = ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt();
+                assert!((length - 1.0).abs() < 1e-4);
+                let mid = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+                assert!((mid[0].powi(2) + mid[1].powi(2)).sqrt() > outer);
+            }
+        }
+    }
+}
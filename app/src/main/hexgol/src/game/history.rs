@@ -0,0 +1,216 @@
+use super::*;
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Change {
+    Generation,
+    Edit,
+}
+
+/// A step in the timeline, stored as the cells it flipped.
+struct Entry {
+    change: Change,
+    cells: Vec<HexInt>,
+}
+impl Entry {
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.cells.capacity() * std::mem::size_of::<HexInt>()
+    }
+}
+
+/// Bounded undo/redo timeline of generations and edits.
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    edit: Option<Vec<HexInt>>,
+    /// Approximate bytes kept before the oldest entries are dropped.
+    budget: usize,
+    used: usize,
+}
+impl History {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: vec![],
+            edit: None,
+            budget,
+            used: 0,
+        }
+    }
+
+    /// Drops the oldest entries if the history no longer fits.
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.trim();
+    }
+
+    pub fn record_generation(&mut self, game: &HexGOL) {
+        self.push(Entry {
+            change: Change::Generation,
+            cells: game.changed().to_vec(),
+        });
+    }
+
+    pub fn begin_edit(&mut self) {
+        self.end_edit();
+        self.edit = Some(vec![]);
+    }
+    pub fn editing(&self) -> bool {
+        self.edit.is_some()
+    }
+    /// Records a cell flipped by the edit in progress.
+    pub fn record_edit(&mut self, hex: HexInt) {
+        if let Some(cells) = &mut self.edit {
+            cells.push(hex);
+        }
+    }
+    pub fn end_edit(&mut self) {
+        if let Some(mut cells) = self.edit.take() {
+            if !cells.is_empty() {
+                cells.shrink_to_fit();
+                self.push(Entry {
+                    change: Change::Edit,
+                    cells,
+                });
+            }
+        }
+    }
+
+    pub fn undo(&mut self, game: &mut HexGOL) -> Option<Change> {
+        self.end_edit();
+
+        let entry = self.undo.pop_back()?;
+        game.flip(&entry.cells);
+        if entry.change == Change::Generation {
+            game.set_generation(game.generation().saturating_sub(1));
+        }
+
+        let change = entry.change;
+        self.redo.push(entry);
+        Some(change)
+    }
+    pub fn redo(&mut self, game: &mut HexGOL) -> Option<Change> {
+        self.end_edit();
+
+        let entry = self.redo.pop()?;
+        game.flip(&entry.cells);
+        if entry.change == Change::Generation {
+            game.set_generation(game.generation() + 1);
+        }
+
+        let change = entry.change;
+        self.undo.push_back(entry);
+        Some(change)
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn push(&mut self, entry: Entry) {
+        for entry in self.redo.drain(..) {
+            self.used -= entry.size();
+        }
+
+        self.used += entry.size();
+        self.undo.push_back(entry);
+        self.trim();
+    }
+    fn trim(&mut self) {
+        while self.used > self.budget {
+            match self.undo.pop_front() {
+                Some(entry) => self.used -= entry.size(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live(game: &HexGOL) -> Vec<HexInt> {
+        let mut cells: Vec<_> = game
+            .iter()
+            .filter(|(_hex, cell)| **cell)
+            .map(|(hex, _cell)| *hex)
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    /// A board with an edit and three generations recorded after it.
+    fn played() -> (HexGOL, History, Vec<Vec<HexInt>>) {
+        let mut game = HexGOL::new(8);
+        let mut history = History::new(usize::MAX);
+        let mut boards = vec![live(&game)];
+
+        history.begin_edit();
+        for hex in [HexInt::new(0, 0), HexInt::new(1, 0), HexInt::new(0, 1)] {
+            game.set(&hex, true);
+            history.record_edit(hex);
+        }
+        history.end_edit();
+        boards.push(live(&game));
+
+        for _ in 0..3 {
+            game.update();
+            history.record_generation(&game);
+            boards.push(live(&game));
+        }
+        (game, history, boards)
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_timeline() {
+        let (mut game, mut history, boards) = played();
+
+        for board in boards.iter().rev().skip(1) {
+            assert!(history.undo(&mut game).is_some());
+            assert_eq!(live(&game), *board);
+        }
+        assert_eq!(history.undo(&mut game), None);
+        assert_eq!(game.generation(), 0);
+
+        assert_eq!(history.redo(&mut game), Some(Change::Edit));
+        for board in &boards[2..] {
+            assert_eq!(history.redo(&mut game), Some(Change::Generation));
+            assert_eq!(live(&game), *board);
+        }
+        assert!(!history.can_redo());
+        assert_eq!(game.generation(), 3);
+    }
+
+    #[test]
+    fn new_changes_drop_the_redo() {
+        let (mut game, mut history, _boards) = played();
+        history.undo(&mut game);
+        assert!(history.can_redo());
+
+        game.update();
+        history.record_generation(&game);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn empty_edits_are_not_recorded() {
+        let mut game = HexGOL::new(4);
+        let mut history = History::new(usize::MAX);
+        history.begin_edit();
+        assert!(history.editing());
+        history.end_edit();
+        assert!(!history.editing());
+        assert_eq!(history.undo(&mut game), None);
+    }
+
+    #[test]
+    fn budget_drops_the_oldest_entries() {
+        let (mut game, mut history, boards) = played();
+        // Room for about one entry.
+        history.set_budget(std::mem::size_of::<Entry>() + 16 * std::mem::size_of::<HexInt>());
+
+        assert_eq!(history.undo(&mut game), Some(Change::Generation));
+        assert_eq!(live(&game), boards[boards.len() - 2]);
+        assert_eq!(history.undo(&mut game), None);
+    }
+}
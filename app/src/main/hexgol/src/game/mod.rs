@@ -1,5 +1,26 @@
 pub mod hex;
 pub use hex::*;
+mod history;
+pub use history::*;
+mod cycle;
+pub use cycle::*;
+mod stats;
+pub use stats::*;
+mod activity;
+pub use activity::*;
+mod rule;
+pub use rule::*;
+mod census;
+pub use census::*;
+mod pattern;
+pub use pattern::*;
+mod selection;
+pub use selection::*;
+mod topology;
+pub use topology::*;
+
+/// Generations kept around to look for repeats in.
+const CYCLE_WINDOW: usize = 4096;
 
 const NEIGHBORS: [HexInt; 6] = [
     HexInt::new(1, 0),
@@ -13,9 +34,21 @@ const NEIGHBORS: [HexInt; 6] = [
 use std::collections::HashMap;
 type GameState = HashMap<HexInt, bool>;
 pub struct HexGOL {
-    // size: i32,
+    size: i32,
     game: GameState,
     game_back: GameState,
+    rule: Rule,
+    neighborhood: Neighborhood,
+    topology: Topology,
+    generation: u64,
+    /// Cells that flipped in the last `update`.
+    changed: Vec<HexInt>,
+    /// XOR of `cell_hash` over the live cells.
+    hash: u64,
+    cycles: CycleDetector,
+    /// Set when cells were set since the last generation, so cycle detection
+    /// starts over once for the whole edit.
+    edited: bool,
 }
 impl HexGOL {
     pub fn new(size: i32) -> Self {
@@ -27,27 +60,119 @@ impl HexGOL {
         }
         let game_back = game.clone();
 
+        let mut cycles = CycleDetector::new(CYCLE_WINDOW);
+        cycles.reset(0, 0);
+
         Self {
-            // size,
+            size,
             game,
             game_back,
+            rule: Rule::default(),
+            neighborhood: Neighborhood::default(),
+            topology: Topology::default(),
+            generation: 0,
+            changed: vec![],
+            hash: 0,
+            cycles,
+            edited: false,
         }
     }
-    pub fn update(&mut self) {
-        for (hex, _state) in &self.game {
-            *self.game_back.get_mut(hex).unwrap() = self.get_num_neighbors(hex) == 2;
+    /// Advances one generation, returning the cycle if this step closed one.
+    pub fn update(&mut self) -> Option<Cycle> {
+        if std::mem::take(&mut self.edited) {
+            self.cycles.reset(self.hash, self.generation);
+        }
+        self.changed.clear();
+        for (hex, state) in &self.game {
+            let next = self.rule.next(*state, self.get_num_neighbors(hex));
+            *self.game_back.get_mut(hex).unwrap() = next;
+            if next != *state {
+                self.changed.push(*hex);
+            }
         }
 
         std::mem::swap(&mut self.game, &mut self.game_back);
+        self.generation += 1;
+
+        for hex in &self.changed {
+            self.hash ^= cell_hash(hex);
+        }
+        let game = &self.game;
+        self.cycles
+            .observe(self.hash, self.generation, || live_cells(game))
     }
     pub fn get(&self, hex: &HexInt) -> Option<&bool> {
         self.game.get(hex)
     }
+    /// Sets a cell, returning whether it changed. Hexes off the board are ignored.
+    pub fn set(&mut self, hex: &HexInt, alive: bool) -> bool {
+        match self.game.get_mut(hex) {
+            Some(cell) if *cell != alive => {
+                *cell = alive;
+                self.hash ^= cell_hash(hex);
+                self.edited = true;
+                true
+            }
+            _ => false,
+        }
+    }
+    pub fn flip(&mut self, cells: &[HexInt]) {
+        for hex in cells {
+            if let Some(cell) = self.game.get_mut(hex) {
+                *cell = !*cell;
+                self.hash ^= cell_hash(hex);
+            }
+        }
+        self.cycles.reset(self.hash, self.generation);
+    }
+    /// Radius of the board in cells.
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.cycles.reset(self.hash, self.generation);
+    }
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.neighborhood = neighborhood;
+        self.cycles.reset(self.hash, self.generation);
+    }
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+        self.cycles.reset(self.hash, self.generation);
+    }
+    pub fn changed(&self) -> &[HexInt] {
+        &self.changed
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+        self.cycles.reset(self.hash, self.generation);
+    }
+    /// The cycle the universe has settled into, if one was seen.
+    pub fn cycle(&self) -> Option<Cycle> {
+        if self.edited {
+            return None;
+        }
+        self.cycles.cycle()
+    }
     pub fn get_num_neighbors(&self, hex: &HexInt) -> i32 {
-        if let Some(_) = self.get(&hex) {
+        if self.get(hex).is_some() {
             let mut num_neighbors = 0;
-            for neighbor_hex in &NEIGHBORS {
-                if let Some(true) = self.get(&(*hex + *neighbor_hex)) {
+            for neighbor_hex in self.neighborhood.offsets() {
+                let neighbor = self.topology.wrap(*hex + *neighbor_hex, self.size);
+                if let Some(true) = neighbor.and_then(|neighbor| self.get(&neighbor)) {
                     num_neighbors += 1;
                 }
             }
@@ -56,17 +181,35 @@ impl HexGOL {
             0
         }
     }
-    pub fn iter(&self) -> std::collections::hash_map::Iter<HexInt, bool> {
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, HexInt, bool> {
         self.game.iter()
     }
 }
 
+/// The live cells of a board, sorted so boards can be compared.
+fn live_cells(game: &GameState) -> Vec<HexInt> {
+    let mut cells: Vec<HexInt> = game
+        .iter()
+        .filter(|(_hex, cell)| **cell)
+        .map(|(hex, _cell)| *hex)
+        .collect();
+    cells.sort();
+    cells
+}
+
 impl HexGOL {
     pub fn randomize(&mut self) {
         use rand::Rng;
         let mut rng = rand::thread_rng();
-        for (_hex, cell) in &mut self.game {
-            *cell = rng.gen() && rng.gen() && rng.gen();
+        self.changed.clear();
+        for (hex, cell) in &mut self.game {
+            let alive = rng.gen() && rng.gen() && rng.gen();
+            if *cell != alive {
+                *cell = alive;
+                self.hash ^= cell_hash(hex);
+                self.changed.push(*hex);
+            }
         }
+        self.cycles.reset(self.hash, self.generation);
     }
 }
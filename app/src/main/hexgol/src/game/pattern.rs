@@ -0,0 +1,209 @@
+use super::*;
+
+/// A named set of live cells, read from and written to a small text format:
+///
+/// ```text
+/// #N Name
+/// #R B2/S34H
+/// #C Any comment
+/// .O.
+/// OO.
+/// ```
+///
+/// Each line below the headers is a row of constant r, each character a
+/// column of increasing q. `O` or `*` is alive, anything else is dead.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub name: String,
+    /// The rule the pattern is meant for, if it names one.
+    pub rule: Option<Rule>,
+    pub comments: Vec<String>,
+    /// Live cells, centred on the origin.
+    pub cells: Vec<HexInt>,
+}
+impl Pattern {
+    pub fn new(name: String, rule: Option<Rule>, cells: Vec<HexInt>) -> Self {
+        let mut pattern = Self {
+            name,
+            rule,
+            comments: vec![],
+            cells,
+        };
+        pattern.center();
+        pattern
+    }
+
+    pub fn parse(text: &str) -> Result<Self, PatternError> {
+        let mut name = String::new();
+        let mut rule = None;
+        let mut comments = vec![];
+        let mut cells = vec![];
+
+        let mut r = 0;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(header) = line.strip_prefix('#') {
+                let mut chars = header.chars();
+                let kind = chars.next();
+                let value = chars.as_str().trim();
+                match kind {
+                    Some('N') => name = value.to_string(),
+                    Some('R') => {
+                        rule = Some(
+                            value
+                                .parse()
+                                .map_err(|err| PatternError::Rule(i + 1, err))?,
+                        )
+                    }
+                    _ => comments.push(value.to_string()),
+                }
+                continue;
+            }
+
+            for (q, c) in line.chars().enumerate() {
+                if c == 'O' || c == '*' {
+                    cells.push(HexInt::new(q as i32, r));
+                }
+            }
+            r += 1;
+        }
+
+        if cells.is_empty() {
+            return Err(PatternError::Empty);
+        }
+
+        let mut pattern = Self::new(name, rule, cells);
+        pattern.comments = comments;
+        Ok(pattern)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.name.is_empty() {
+            text += &format!("#N {}\n", self.name);
+        }
+        if let Some(rule) = self.rule {
+            text += &format!("#R {}\n", rule);
+        }
+        for comment in &self.comments {
+            text += &format!("#C {}\n", comment);
+        }
+
+        let min_q = self.cells.iter().map(|hex| hex.q()).min().unwrap_or(0);
+        let max_q = self.cells.iter().map(|hex| hex.q()).max().unwrap_or(0);
+        let min_r = self.cells.iter().map(|hex| hex.r()).min().unwrap_or(0);
+        let max_r = self.cells.iter().map(|hex| hex.r()).max().unwrap_or(0);
+        for r in min_r..=max_r {
+            let mut row: String = (min_q..=max_q)
+                .map(|q| {
+                    if self.cells.contains(&HexInt::new(q, r)) {
+                        'O'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            row.truncate(row.trim_end_matches('.').len());
+            text += &row;
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The cells after `rotations` steps of 60° and an optional mirror.
+    pub fn transformed(&self, rotations: u8, mirror: bool) -> Vec<HexInt> {
+        self.cells
+            .iter()
+            .map(|hex| hex.transform(rotations, mirror))
+            .collect()
+    }
+
+    /// Moves the cells so their centre of mass is on the origin, which keeps
+    /// them in place when rotated.
+    fn center(&mut self) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let n = self.cells.len() as f32;
+        let q = self.cells.iter().map(|hex| hex.q() as f32).sum::<f32>() / n;
+        let r = self.cells.iter().map(|hex| hex.r() as f32).sum::<f32>() / n;
+        let center = HexInt::from(HexFract::new(q, r).round());
+
+        for hex in &mut self.cells {
+            *hex = *hex - center;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PatternError {
+    /// No live cells.
+    Empty,
+    /// The `#R` header on this line isn't a rule.
+    Rule(usize, ParseRuleError),
+}
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "pattern has no live cells"),
+            Self::Rule(line, err) => write!(f, "line {}: {}", line, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut cells: Vec<HexInt>) -> Vec<HexInt> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "#N Glider\n#R B2/S34H\n#C Moves right\n.O\nO.O\n..OO\n";
+        let pattern = Pattern::parse(text).expect("pattern parses");
+        assert_eq!(pattern.name, "Glider");
+        assert_eq!(pattern.rule, Some("B2/S34".parse().unwrap()));
+        assert_eq!(pattern.comments, ["Moves right"]);
+        assert_eq!(pattern.to_text(), text);
+
+        let again = Pattern::parse(&pattern.to_text()).expect("pattern parses");
+        assert_eq!(sorted(again.cells), sorted(pattern.cells));
+    }
+
+    #[test]
+    fn centres_the_cells() {
+        let pattern = Pattern::parse("OOO\n").expect("pattern parses");
+        assert_eq!(
+            sorted(pattern.cells),
+            [HexInt::new(-1, 0), HexInt::new(0, 0), HexInt::new(1, 0)]
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(
+            Pattern::parse("#N Nothing\n...\n").unwrap_err(),
+            PatternError::Empty
+        );
+        assert_eq!(
+            Pattern::parse("#N Bad\n#R B2\nO\n").unwrap_err(),
+            PatternError::Rule(2, ParseRuleError::Malformed)
+        );
+    }
+
+    #[test]
+    fn parses_the_shipped_patterns() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/patterns");
+        for entry in std::fs::read_dir(dir).expect("patterns are next to the crate") {
+            let path = entry.unwrap().path();
+            let text = std::fs::read_to_string(&path).unwrap();
+            let pattern =
+                Pattern::parse(&text).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+            assert!(!pattern.name.is_empty(), "{} has no name", path.display());
+        }
+    }
+}
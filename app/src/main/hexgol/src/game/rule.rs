@@ -0,0 +1,161 @@
+/// Birth/survival rule as bitmasks over neighbour counts.
+///
+/// Written as `B2/S34H`, one character per count. The extended neighbourhood
+/// has up to 12 neighbours, so counts 10 to 12 are written `a` to `c`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: u32,
+    survive: u32,
+}
+impl Rule {
+    pub const fn new(birth: u32, survive: u32) -> Self {
+        Self { birth, survive }
+    }
+    pub const fn birth(&self) -> u32 {
+        self.birth
+    }
+    pub const fn survive(&self) -> u32 {
+        self.survive
+    }
+
+    pub fn next(&self, alive: bool, neighbors: i32) -> bool {
+        let mask = if alive { self.survive } else { self.birth };
+        mask >> neighbors & 1 == 1
+    }
+}
+impl Default for Rule {
+    /// B2/S2: a cell is alive next generation iff it has exactly two live neighbours.
+    fn default() -> Self {
+        Self::new(1 << 2, 1 << 2)
+    }
+}
+/// Most neighbours a cell can have, with the extended neighbourhood.
+const MAX_NEIGHBORS: u32 = 12;
+/// Base the counts are written in, so each fits in one character.
+const COUNT_RADIX: u32 = MAX_NEIGHBORS + 1;
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |mask: u32| -> String {
+            (0..=MAX_NEIGHBORS)
+                .filter(|n| mask >> n & 1 == 1)
+                .filter_map(|n| std::char::from_digit(n, COUNT_RADIX))
+                .collect()
+        };
+        write!(f, "B{}/S{}H", counts(self.birth), counts(self.survive))
+    }
+}
+impl std::str::FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parses `B<counts>/S<counts>` in either order, with an optional trailing
+    /// `H`. Counts are `0` to `9` and `a` to `c` in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_suffix('H')
+            .or_else(|| s.strip_suffix('h'))
+            .unwrap_or(s);
+
+        let mut birth = None;
+        let mut survive = None;
+        for part in s.split('/') {
+            let mut chars = part.chars();
+            let slot = match chars.next() {
+                Some('B') | Some('b') => &mut birth,
+                Some('S') | Some('s') => &mut survive,
+                _ => return Err(ParseRuleError::Malformed),
+            };
+            if slot.is_some() {
+                return Err(ParseRuleError::Malformed);
+            }
+
+            let mut mask = 0;
+            for c in chars {
+                let n = c.to_digit(COUNT_RADIX).ok_or(ParseRuleError::BadDigit(c))?;
+                mask |= 1 << n;
+            }
+            *slot = Some(mask);
+        }
+
+        match (birth, survive) {
+            (Some(birth), Some(survive)) => Ok(Self::new(birth, survive)),
+            _ => Err(ParseRuleError::Malformed),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseRuleError {
+    /// Not of the form `B.../S...`.
+    Malformed,
+    /// Not a neighbour count from `0` to `c`.
+    BadDigit(char),
+}
+impl std::fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "expected a rule like B2/S34"),
+            Self::BadDigit(c) => write!(f, "'{}' is not a neighbour count", c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_text() {
+        for rule in [
+            Rule::default(),
+            Rule::new(0, 0),
+            Rule::new(1 << 2, 1 << 3 | 1 << 4),
+            Rule::new(0b11_1111_1110, 0b1),
+        ] {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+        assert_eq!(Rule::default().to_string(), "B2/S2H");
+    }
+
+    #[test]
+    fn writes_every_neighbour_count() {
+        for n in 0..=MAX_NEIGHBORS {
+            let rule = Rule::new(1 << n, 1 << (MAX_NEIGHBORS - n));
+            assert_eq!(rule.to_string().parse(), Ok(rule), "{}", rule);
+        }
+        let all = Rule::new(0x1fff, 0x1fff);
+        assert_eq!(all.to_string(), "B0123456789abc/S0123456789abcH");
+        assert_eq!(all.to_string().parse(), Ok(all));
+        assert_eq!(
+            "B2A/S3bC".parse(),
+            Ok(Rule::new(1 << 2 | 1 << 10, 1 << 3 | 1 << 11 | 1 << 12))
+        );
+    }
+
+    #[test]
+    fn parses_loosely() {
+        let rule = Rule::new(1 << 2, 1 << 3 | 1 << 4);
+        for text in ["B2/S34", "b2/s34h", " S34/B2H ", "B2/S43"] {
+            assert_eq!(text.parse(), Ok(rule), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_nonsense() {
+        assert_eq!("B2".parse::<Rule>(), Err(ParseRuleError::Malformed));
+        assert_eq!("B2/B3".parse::<Rule>(), Err(ParseRuleError::Malformed));
+        assert_eq!("X2/S3".parse::<Rule>(), Err(ParseRuleError::Malformed));
+        assert_eq!("B2/S3d".parse::<Rule>(), Err(ParseRuleError::BadDigit('d')));
+        assert_eq!("B2/S3-".parse::<Rule>(), Err(ParseRuleError::BadDigit('-')));
+    }
+
+    #[test]
+    fn applies_birth_and_survival() {
+        let rule: Rule = "B2/S34".parse().unwrap();
+        assert!(rule.next(false, 2));
+        assert!(!rule.next(false, 3));
+        assert!(rule.next(true, 3) && rule.next(true, 4));
+        assert!(!rule.next(true, 2));
+    }
+}
@@ -0,0 +1,227 @@
+use super::*;
+use std::collections::HashSet;
+
+/// A region of the board.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Selection {
+    /// Hexes whose centres lie in the screen-aligned rectangle spanned by the
+    /// centres of two corner hexes.
+    Rect { a: HexInt, b: HexInt },
+    /// Hexes within `radius` steps of `center`.
+    Hex { center: HexInt, radius: i32 },
+}
+impl Selection {
+    pub fn contains(&self, hex: &HexInt) -> bool {
+        match self {
+            Self::Rect { a, b } => {
+                let a = HexFract::from(*a).transform(1.0);
+                let b = HexFract::from(*b).transform(1.0);
+                let p = HexFract::from(*hex).transform(1.0);
+                // Slack for rounding on the edges.
+                let inside = |axis: usize| {
+                    p[axis] >= a[axis].min(b[axis]) - 1e-3 && p[axis] <= a[axis].max(b[axis]) + 1e-3
+                };
+                inside(0) && inside(1)
+            }
+            Self::Hex { center, radius } => center.distance(hex) <= *radius,
+        }
+    }
+    /// The hex transforms turn around.
+    pub fn center(&self) -> HexInt {
+        match self {
+            Self::Rect { a, b } => {
+                let q = (a.q() + b.q()) as f32 / 2.0;
+                let r = (a.r() + b.r()) as f32 / 2.0;
+                HexInt::from(HexFract::new(q, r).round())
+            }
+            Self::Hex { center, .. } => *center,
+        }
+    }
+}
+
+/// Clipboard operations. Edits return the cells they flipped so they can be
+/// recorded in the history.
+impl HexGOL {
+    /// The live cells of a region, centred like any other pattern.
+    pub fn copy(&self, selection: &Selection) -> Pattern {
+        Pattern::new(String::new(), None, self.selected(selection))
+    }
+    pub fn cut(&mut self, selection: &Selection) -> (Pattern, Vec<HexInt>) {
+        (self.copy(selection), self.clear(selection))
+    }
+    /// Brings cells to life. Cells off the board are dropped.
+    pub fn paste(&mut self, cells: &[HexInt]) -> Vec<HexInt> {
+        cells
+            .iter()
+            .filter(|hex| self.set(hex, true))
+            .copied()
+            .collect()
+    }
+    pub fn clear(&mut self, selection: &Selection) -> Vec<HexInt> {
+        let cells = self.selected(selection);
+        self.flip(&cells);
+        cells
+    }
+    /// Turns a region's live cells `rotations` steps of 60° about its centre.
+    pub fn rotate(&mut self, selection: &Selection, rotations: u8) -> Vec<HexInt> {
+        self.transform(selection, rotations, false)
+    }
+    /// Reflects a region's live cells across the horizontal line through its centre.
+    pub fn mirror(&mut self, selection: &Selection) -> Vec<HexInt> {
+        self.transform(selection, 0, true)
+    }
+
+    fn transform(&mut self, selection: &Selection, rotations: u8, mirror: bool) -> Vec<HexInt> {
+        let center = selection.center();
+        let old: HashSet<HexInt> = self.selected(selection).into_iter().collect();
+        let new: HashSet<HexInt> = old
+            .iter()
+            .map(|hex| (*hex - center).transform(rotations, mirror) + center)
+            .filter(|hex| self.get(hex).is_some())
+            .collect();
+
+        // Cells landing on live cells outside the region just stay alive.
+        let changed: Vec<HexInt> = old
+            .difference(&new)
+            .chain(new.iter().filter(|hex| self.get(hex) == Some(&false)))
+            .copied()
+            .collect();
+        self.flip(&changed);
+        changed
+    }
+
+    fn selected(&self, selection: &Selection) -> Vec<HexInt> {
+        self.iter()
+            .filter(|(hex, cell)| **cell && selection.contains(hex))
+            .map(|(hex, _cell)| *hex)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut cells: Vec<HexInt>) -> Vec<HexInt> {
+        cells.sort();
+        cells
+    }
+
+    fn live(game: &HexGOL) -> Vec<HexInt> {
+        sorted(
+            game.iter()
+                .filter(|(_hex, cell)| **cell)
+                .map(|(hex, _cell)| *hex)
+                .collect(),
+        )
+    }
+
+    /// A board of radius 3 with four cells along its right edge and one in the
+    /// middle.
+    fn edge() -> HexGOL {
+        let mut game = HexGOL::new(3);
+        for r in -3..=0 {
+            game.set(&HexInt::new(3, r), true);
+        }
+        game.set(&HexInt::new(0, 0), true);
+        game
+    }
+
+    #[test]
+    fn selects_by_shape() {
+        let hex = Selection::Hex {
+            center: HexInt::new(3, -1),
+            radius: 1,
+        };
+        assert!(hex.contains(&HexInt::new(3, -2)) && hex.contains(&HexInt::new(4, -2)));
+        assert!(!hex.contains(&HexInt::new(1, -1)));
+
+        let rect = Selection::Rect {
+            a: HexInt::new(3, -3),
+            b: HexInt::new(3, 0),
+        };
+        // A column of hexes from the top right corner down.
+        assert!(rect.contains(&HexInt::new(3, -2)));
+        assert!(!rect.contains(&HexInt::new(2, -1)));
+        assert_eq!(rect.center(), HexInt::new(3, -2));
+    }
+
+    #[test]
+    fn copies_and_cuts_at_the_edge() {
+        let mut game = edge();
+        let selection = Selection::Hex {
+            center: HexInt::new(3, -2),
+            radius: 1,
+        };
+
+        let copied = game.copy(&selection);
+        assert_eq!(
+            sorted(copied.cells.clone()),
+            [HexInt::new(0, -1), HexInt::new(0, 0), HexInt::new(0, 1)]
+        );
+        assert_eq!(live(&game).len(), 5);
+
+        let (cut, flipped) = game.cut(&selection);
+        assert_eq!(sorted(cut.cells), sorted(copied.cells));
+        assert_eq!(
+            sorted(flipped),
+            [HexInt::new(3, -3), HexInt::new(3, -2), HexInt::new(3, -1)]
+        );
+        assert_eq!(live(&game), [HexInt::new(0, 0), HexInt::new(3, 0)]);
+    }
+
+    #[test]
+    fn pastes_only_onto_the_board() {
+        let mut game = HexGOL::new(3);
+        game.set(&HexInt::new(2, 0), true);
+        let cells: Vec<HexInt> = (1..=5).map(|q| HexInt::new(q, 0)).collect();
+
+        // Cells off the board and ones already alive aren't flipped.
+        let flipped = game.paste(&cells);
+        assert_eq!(sorted(flipped), [HexInt::new(1, 0), HexInt::new(3, 0)]);
+        assert_eq!(
+            live(&game),
+            [HexInt::new(1, 0), HexInt::new(2, 0), HexInt::new(3, 0)]
+        );
+    }
+
+    #[test]
+    fn rotating_off_the_board_drops_cells() {
+        let mut game = edge();
+        let selection = Selection::Hex {
+            center: HexInt::new(3, -1),
+            radius: 1,
+        };
+        let before = live(&game);
+        let flipped = game.rotate(&selection, 1);
+
+        // Every flip is recorded, and everything ends up on the board.
+        let mut after = before.clone();
+        for hex in &flipped {
+            match after.iter().position(|h| h == hex) {
+                Some(i) => {
+                    after.remove(i);
+                }
+                None => after.push(*hex),
+            }
+        }
+        assert_eq!(sorted(after), live(&game));
+        assert!(live(&game).iter().all(|hex| game.get(hex).is_some()));
+        assert!(live(&game).len() < before.len());
+    }
+
+    #[test]
+    fn mirroring_twice_restores_the_region() {
+        let mut game = edge();
+        let selection = Selection::Rect {
+            a: HexInt::new(-1, -1),
+            b: HexInt::new(1, 1),
+        };
+        game.set(&HexInt::new(1, -1), true);
+        let before = live(&game);
+        assert!(!game.mirror(&selection).is_empty());
+        assert_ne!(live(&game), before);
+        game.mirror(&selection);
+        assert_eq!(live(&game), before);
+    }
+}
@@ -0,0 +1,186 @@
+use super::*;
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: u32,
+    pub births: u32,
+    pub deaths: u32,
+    /// Distance from the centre of the board to the furthest live cell.
+    pub radius: u32,
+}
+impl GenerationStats {
+    pub fn new(game: &HexGOL) -> Self {
+        let origin = HexInt::new(0, 0);
+
+        let mut stats = Self {
+            generation: game.generation(),
+            ..Default::default()
+        };
+        for (hex, cell) in game.iter() {
+            if *cell {
+                stats.population += 1;
+                stats.radius = stats.radius.max(hex.distance(&origin) as u32);
+            }
+        }
+        for hex in game.changed() {
+            if let Some(true) = game.get(hex) {
+                stats.births += 1;
+            } else {
+                stats.deaths += 1;
+            }
+        }
+
+        stats
+    }
+}
+
+/// Ring buffer of the most recent generations' statistics.
+pub struct Stats {
+    history: VecDeque<GenerationStats>,
+    capacity: usize,
+}
+impl Stats {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records the current generation. Anything at or after it is dropped first,
+    /// so stepping back and replaying doesn't leave stale samples.
+    pub fn record(&mut self, game: &HexGOL) {
+        let stats = GenerationStats::new(game);
+
+        while let Some(last) = self.history.back() {
+            if last.generation < stats.generation {
+                break;
+            }
+            self.history.pop_back();
+        }
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(stats);
+    }
+
+    /// Records the current generation again after the board was edited or
+    /// stepped back. Births and deaths are kept from the last sample of this
+    /// generation, since edits aren't part of it.
+    pub fn resample(&mut self, game: &HexGOL) {
+        let previous = self
+            .history
+            .iter()
+            .rfind(|stats| stats.generation == game.generation())
+            .copied()
+            .unwrap_or_default();
+        self.record(game);
+        if let Some(stats) = self.history.back_mut() {
+            stats.births = previous.births;
+            stats.deaths = previous.deaths;
+        }
+    }
+
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, GenerationStats> {
+        self.history.iter()
+    }
+
+    /// One field of every sample, scaled so the largest in `max_of` is 1.
+    pub fn normalized<F, M>(&self, field: F, max_of: M) -> Vec<f32>
+    where
+        F: Fn(&GenerationStats) -> u32,
+        M: Fn(&GenerationStats) -> u32,
+    {
+        let max = self.iter().map(max_of).max().unwrap_or(0).max(1) as f32;
+        self.iter().map(|stats| field(stats) as f32 / max).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two cells with one neighbour in common, which is born as they die.
+    fn pair() -> HexGOL {
+        let mut game = HexGOL::new(6);
+        game.set(&HexInt::new(0, 0), true);
+        game.set(&HexInt::new(2, 0), true);
+        game
+    }
+
+    #[test]
+    fn counts_the_generation() {
+        let mut game = pair();
+        let stats = GenerationStats::new(&game);
+        assert_eq!(stats.population, 2);
+        assert_eq!(stats.radius, 2);
+
+        game.update();
+        let stats = GenerationStats::new(&game);
+        assert_eq!(stats.generation, 1);
+        assert_eq!(stats.population, 1);
+        assert_eq!((stats.births, stats.deaths), (1, 2));
+        assert_eq!(stats.radius, 1);
+    }
+
+    #[test]
+    fn keeps_the_latest_generations() {
+        let mut game = pair();
+        let mut stats = Stats::new(3);
+        for _ in 0..5 {
+            stats.record(&game);
+            game.update();
+        }
+        let generations: Vec<u64> = stats.iter().map(|s| s.generation).collect();
+        assert_eq!(generations, [2, 3, 4]);
+    }
+
+    #[test]
+    fn stepping_back_drops_later_samples() {
+        let mut game = pair();
+        let mut stats = Stats::new(8);
+        for _ in 0..4 {
+            stats.record(&game);
+            game.update();
+        }
+        game.set_generation(1);
+        stats.record(&game);
+        let generations: Vec<u64> = stats.iter().map(|s| s.generation).collect();
+        assert_eq!(generations, [0, 1]);
+    }
+
+    #[test]
+    fn resampling_keeps_births_and_deaths() {
+        let mut game = pair();
+        let mut stats = Stats::new(8);
+        game.update();
+        stats.record(&game);
+        let before = *stats.iter().last().unwrap();
+
+        game.set(&HexInt::new(-4, 0), true);
+        stats.resample(&game);
+        let after = *stats.iter().last().unwrap();
+        assert_eq!(stats.iter().len(), 1);
+        assert_eq!(after.population, before.population + 1);
+        assert_eq!((after.births, after.deaths), (before.births, before.deaths));
+    }
+
+    #[test]
+    fn normalizes_against_the_largest() {
+        let mut game = HexGOL::new(4);
+        let mut stats = Stats::new(8);
+        for q in 0..4 {
+            game.set(&HexInt::new(q, 0), true);
+            game.set_generation(q as u64);
+            stats.record(&game);
+        }
+        let values = stats.normalized(|s| s.population, |s| s.population);
+        assert_eq!(values, [0.25, 0.5, 0.75, 1.0]);
+
+        // An empty board doesn't divide by zero.
+        let stats = Stats::new(8);
+        assert!(stats.normalized(|s| s.births, |s| s.births).is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+
+/// The six cells two steps away between a pair of adjacent ones.
+const DIAGONALS: [HexInt; 6] = [
+    HexInt::new(2, -1),
+    HexInt::new(1, 1),
+    HexInt::new(-1, 2),
+    HexInt::new(-2, 1),
+    HexInt::new(-1, -1),
+    HexInt::new(1, -2),
+];
+
+/// Which cells count towards a cell's neighbours.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The six cells sharing an edge.
+    #[default]
+    Adjacent,
+    /// The adjacent cells and the six diagonal ones behind their corners.
+    Extended,
+}
+impl Neighborhood {
+    pub const ALL: [Self; 2] = [Self::Adjacent, Self::Extended];
+
+    pub fn offsets(&self) -> &'static [HexInt] {
+        const EXTENDED: [HexInt; 12] = [
+            NEIGHBORS[0],
+            NEIGHBORS[1],
+            NEIGHBORS[2],
+            NEIGHBORS[3],
+            NEIGHBORS[4],
+            NEIGHBORS[5],
+            DIAGONALS[0],
+            DIAGONALS[1],
+            DIAGONALS[2],
+            DIAGONALS[3],
+            DIAGONALS[4],
+            DIAGONALS[5],
+        ];
+        match self {
+            Self::Adjacent => &NEIGHBORS,
+            Self::Extended => &EXTENDED,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Adjacent => "adjacent",
+            Self::Extended => "extended",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|n| n.name() == name)
+    }
+}
+
+/// What lies past the edge of the board.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Dead cells.
+    #[default]
+    Bounded,
+    /// The opposite edge, so the board tiles the plane.
+    Wrapped,
+}
+impl Topology {
+    pub const ALL: [Self; 2] = [Self::Bounded, Self::Wrapped];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bounded => "bounded",
+            Self::Wrapped => "wrapped",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// The board cell a hex stands for, if any. `hex` may be at most `size`
+    /// steps off the board.
+    pub fn wrap(&self, hex: HexInt, size: i32) -> Option<HexInt> {
+        let origin = HexInt::new(0, 0);
+        if hex.distance(&origin) <= size {
+            return Some(hex);
+        }
+        if *self == Self::Bounded {
+            return None;
+        }
+
+        // Copies of the board around this one are centred on the rotations of
+        // this hex.
+        let center = HexInt::new(2 * size + 1, -size);
+        (0..6)
+            .map(|rotations| hex - center.transform(rotations, false))
+            .find(|hex| hex.distance(&origin) <= size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(size: i32) -> Vec<HexInt> {
+        let origin = HexInt::new(0, 0);
+        (-size..=size)
+            .flat_map(|q| (-size..=size).map(move |r| HexInt::new(q, r)))
+            .filter(|hex| hex.distance(&origin) <= size)
+            .collect()
+    }
+
+    #[test]
+    fn bounded_drops_what_is_off_the_board() {
+        let size = 3;
+        assert_eq!(
+            Topology::Bounded.wrap(HexInt::new(3, 0), size),
+            Some(HexInt::new(3, 0))
+        );
+        assert_eq!(Topology::Bounded.wrap(HexInt::new(4, 0), size), None);
+    }
+
+    #[test]
+    fn wrapped_lands_every_neighbour_on_the_board() {
+        let origin = HexInt::new(0, 0);
+        // Extended neighbours need the board to be at least two cells across
+        // to stay distinct.
+        for size in 2..8 {
+            for hex in board(size) {
+                let mut neighbors = vec![];
+                for offset in Neighborhood::Extended.offsets() {
+                    let neighbor = Topology::Wrapped
+                        .wrap(hex + *offset, size)
+                        .expect("wrapped neighbour is on the board");
+                    assert!(neighbor.distance(&origin) <= size);
+                    neighbors.push(neighbor);
+                }
+                neighbors.sort();
+                neighbors.dedup();
+                assert_eq!(neighbors.len(), 12, "size {} hex {:?}", size, hex);
+                assert!(!neighbors.contains(&hex));
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_is_symmetric() {
+        // If b is a's neighbour across the edge, a is b's across the other.
+        let size = 4;
+        for hex in board(size) {
+            for offset in &NEIGHBORS {
+                let neighbor = Topology::Wrapped.wrap(hex + *offset, size).unwrap();
+                let back = Topology::Wrapped.wrap(neighbor - *offset, size).unwrap();
+                assert_eq!(back, hex);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::renderer::{text_size, Label};
+use std::time::{Duration, Instant};
+
+/// Window pixels per font pixel.
+const SCALE: f32 = 4.0;
+/// Space between the text and the edges of the usable area, in pixels.
+const MARGIN: f32 = 12.0;
+const LINE_GAP: f32 = 8.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Edges of the window covered by system bars and cutouts, in pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+/// Lines of text pinned to the corners of the window, inside the insets. Lines
+/// in a corner stack away from it in the order they were pushed.
+pub struct Hud {
+    lines: Vec<(Corner, String)>,
+    /// Pixels kept clear next to each corner.
+    reserved: [f32; 4],
+}
+impl Default for Hud {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Hud {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![],
+            reserved: [0.0; 4],
+        }
+    }
+
+    /// Keeps lines in a corner `pixels` away from it, clear of other overlays.
+    pub fn reserve(&mut self, corner: Corner, pixels: f32) {
+        let reserved = &mut self.reserved[corner as usize];
+        *reserved = reserved.max(pixels);
+    }
+
+    pub fn push(&mut self, corner: Corner, text: String) {
+        self.lines.push((corner, text));
+    }
+
+    pub fn labels(&self, size: [u32; 2], insets: Insets, color: [f32; 3]) -> Vec<Label> {
+        let left = insets.left + MARGIN;
+        let top = insets.top + MARGIN;
+        let right = size[0] as f32 - insets.right - MARGIN;
+        let bottom = size[1] as f32 - insets.bottom - MARGIN;
+
+        let mut stacked = self.reserved;
+        self.lines
+            .iter()
+            .map(|(corner, text)| {
+                let [width, height] = text_size(text, SCALE);
+                let offset = &mut stacked[*corner as usize];
+                let x = match corner {
+                    Corner::TopLeft | Corner::BottomLeft => left,
+                    Corner::TopRight | Corner::BottomRight => right - width,
+                };
+                let y = match corner {
+                    Corner::TopLeft | Corner::TopRight => top + *offset,
+                    Corner::BottomLeft | Corner::BottomRight => bottom - *offset - height,
+                };
+                *offset += height + LINE_GAP;
+
+                Label {
+                    text: text.clone(),
+                    pos: [x, y],
+                    scale: SCALE,
+                    color,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Frames per second, counted over about a second.
+pub struct FrameCounter {
+    frames: u32,
+    since: Instant,
+    fps: f32,
+}
+impl Default for FrameCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl FrameCounter {
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            since: Instant::now(),
+            fps: 0.0,
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.frames += 1;
+        let elapsed = self.since.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.frames = 0;
+            self.since = Instant::now();
+        }
+    }
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: [u32; 2] = [800, 600];
+
+    fn insets() -> Insets {
+        Insets {
+            left: 10.0,
+            top: 40.0,
+            right: 20.0,
+            bottom: 30.0,
+        }
+    }
+
+    #[test]
+    fn pins_lines_inside_the_insets() {
+        let mut hud = Hud::new();
+        for corner in [
+            Corner::TopLeft,
+            Corner::TopRight,
+            Corner::BottomLeft,
+            Corner::BottomRight,
+        ] {
+            hud.push(corner, "Gen 12".to_string());
+        }
+        let labels = hud.labels(SIZE, insets(), [1.0; 3]);
+        let [width, height] = text_size("Gen 12", SCALE);
+
+        let left = 10.0 + MARGIN;
+        let top = 40.0 + MARGIN;
+        let right = 800.0 - 20.0 - MARGIN - width;
+        let bottom = 600.0 - 30.0 - MARGIN - height;
+        let positions: Vec<[f32; 2]> = labels.iter().map(|label| label.pos).collect();
+        assert_eq!(
+            positions,
+            [[left, top], [right, top], [left, bottom], [right, bottom]]
+        );
+        assert!(labels.iter().all(|label| label.scale == SCALE));
+    }
+
+    #[test]
+    fn stacks_lines_away_from_the_corner() {
+        let mut hud = Hud::new();
+        hud.push(Corner::TopLeft, "Gen 1".to_string());
+        hud.push(Corner::BottomRight, "60 fps".to_string());
+        hud.push(Corner::TopLeft, "Pop 7".to_string());
+        hud.push(Corner::BottomRight, "B2/S2H".to_string());
+        let labels = hud.labels(SIZE, Insets::default(), [1.0; 3]);
+
+        let height = text_size("Gen 1", SCALE)[1];
+        assert_eq!(labels[2].pos[1] - labels[0].pos[1], height + LINE_GAP);
+        assert_eq!(labels[1].pos[1] - labels[3].pos[1], height + LINE_GAP);
+        // Right-aligned lines end at the same place whatever their length.
+        let end = |label: &Label| label.pos[0] + text_size(&label.text, SCALE)[0];
+        assert_eq!(end(&labels[1]), end(&labels[3]));
+    }
+
+    #[test]
+    fn keeps_clear_of_reserved_space() {
+        let mut hud = Hud::new();
+        hud.reserve(Corner::TopRight, 100.0);
+        hud.reserve(Corner::TopRight, 50.0);
+        hud.push(Corner::TopRight, "Paused".to_string());
+        hud.push(Corner::TopLeft, "Gen 1".to_string());
+        let labels = hud.labels(SIZE, Insets::default(), [1.0; 3]);
+
+        assert_eq!(labels[0].pos[1], MARGIN + 100.0);
+        assert_eq!(labels[1].pos[1], MARGIN);
+    }
+}
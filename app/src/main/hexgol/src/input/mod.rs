@@ -0,0 +1,378 @@
+use std::time::{Duration, Instant};
+
+/// How far fingers may travel, in pixels, before a touch stops being a tap.
+const TOUCH_SLOP: f32 = 24.0;
+const TAP_TIMEOUT: Duration = Duration::from_millis(300);
+
+#[derive(Copy, Clone, Debug)]
+pub struct Pointer {
+    pub id: i32,
+    pub pos: [f32; 2],
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum TouchPhase {
+    Down(i32),
+    Move,
+    Up(i32),
+    Cancel,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Gesture {
+    DragStart {
+        pos: [f32; 2],
+    },
+    Drag {
+        pos: [f32; 2],
+        delta: [f32; 2],
+    },
+    DragEnd,
+    Pinch {
+        focus: [f32; 2],
+        delta: [f32; 2],
+        scale: f32,
+        /// Counter-clockwise twist on screen, in radians.
+        rotation: f32,
+    },
+    Tap {
+        pos: [f32; 2],
+        fingers: usize,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Mode {
+    Idle,
+    Drag,
+    Pinch,
+    /// Fingers were lifted out of a pinch; ignore the rest of the touch.
+    Done,
+}
+
+pub struct Gestures {
+    pointers: Vec<Pointer>,
+    mode: Mode,
+    start: Instant,
+    start_pos: [f32; 2],
+    travelled: f32,
+    fingers: usize,
+}
+impl Default for Gestures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Gestures {
+    pub fn new() -> Self {
+        Self {
+            pointers: vec![],
+            mode: Mode::Idle,
+            start: Instant::now(),
+            start_pos: [0.0, 0.0],
+            travelled: 0.0,
+            fingers: 0,
+        }
+    }
+
+    /// Whether fingers are on the screen.
+    pub fn active(&self) -> bool {
+        !self.pointers.is_empty()
+    }
+
+    /// Feeds one motion event. `pointers` holds every pointer of the event,
+    /// including the one going up.
+    pub fn handle(&mut self, phase: TouchPhase, pointers: &[Pointer], out: &mut Vec<Gesture>) {
+        match phase {
+            TouchPhase::Down(id) => {
+                if self.pointers.is_empty() {
+                    self.mode = Mode::Idle;
+                    self.start = Instant::now();
+                    self.start_pos = find(pointers, id).map_or([0.0, 0.0], |p| p.pos);
+                    self.travelled = 0.0;
+                    self.fingers = 0;
+                } else if self.mode == Mode::Drag {
+                    out.push(Gesture::DragEnd);
+                    self.mode = Mode::Pinch;
+                }
+                self.pointers = pointers.to_vec();
+                self.fingers = self.fingers.max(self.pointers.len());
+            }
+            TouchPhase::Move => {
+                let (old_centre, old_spread) = centre_spread(&self.pointers, pointers);
+                let (new_centre, new_spread) = centre_spread(pointers, &self.pointers);
+                let delta = [new_centre[0] - old_centre[0], new_centre[1] - old_centre[1]];
+                let rotation = match (
+                    angle(&self.pointers, pointers),
+                    angle(pointers, &self.pointers),
+                ) {
+                    (Some(old), Some(new)) => wrap_angle(new - old),
+                    _ => 0.0,
+                };
+                // Spreading fingers counts too, or a pinch about a still
+                // centre would never start.
+                self.travelled += (delta[0] * delta[0] + delta[1] * delta[1]).sqrt()
+                    + (new_spread - old_spread).abs();
+                self.pointers = pointers.to_vec();
+
+                if self.mode == Mode::Idle && self.travelled > TOUCH_SLOP {
+                    if pointers.len() == 1 {
+                        self.mode = Mode::Drag;
+                        out.push(Gesture::DragStart {
+                            pos: self.start_pos,
+                        });
+                    } else {
+                        self.mode = Mode::Pinch;
+                    }
+                }
+
+                match self.mode {
+                    Mode::Drag => out.push(Gesture::Drag {
+                        pos: new_centre,
+                        delta,
+                    }),
+                    Mode::Pinch if old_spread > 0.0 && new_spread > 0.0 => {
+                        out.push(Gesture::Pinch {
+                            focus: new_centre,
+                            delta,
+                            scale: new_spread / old_spread,
+                            rotation,
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::Up(id) => {
+                self.pointers.retain(|p| p.id != id);
+                if !self.pointers.is_empty() {
+                    if self.mode == Mode::Pinch {
+                        self.mode = Mode::Done;
+                    }
+                    return;
+                }
+                match self.mode {
+                    Mode::Drag => out.push(Gesture::DragEnd),
+                    Mode::Idle if self.start.elapsed() < TAP_TIMEOUT => out.push(Gesture::Tap {
+                        pos: self.start_pos,
+                        fingers: self.fingers,
+                    }),
+                    _ => {}
+                }
+                self.mode = Mode::Idle;
+            }
+            TouchPhase::Cancel => {
+                if self.mode == Mode::Drag {
+                    out.push(Gesture::DragEnd);
+                }
+                self.pointers.clear();
+                self.mode = Mode::Idle;
+            }
+        }
+    }
+}
+
+fn find(pointers: &[Pointer], id: i32) -> Option<&Pointer> {
+    pointers.iter().find(|p| p.id == id)
+}
+
+/// Centre and mean distance from it of the pointers that are also in `other`.
+fn centre_spread(pointers: &[Pointer], other: &[Pointer]) -> ([f32; 2], f32) {
+    let shared: Vec<[f32; 2]> = pointers
+        .iter()
+        .filter(|p| find(other, p.id).is_some())
+        .map(|p| p.pos)
+        .collect();
+    if shared.is_empty() {
+        return ([0.0, 0.0], 0.0);
+    }
+
+    let n = shared.len() as f32;
+    let mut centre = [0.0, 0.0];
+    for pos in &shared {
+        centre[0] += pos[0] / n;
+        centre[1] += pos[1] / n;
+    }
+    let mut spread = 0.0;
+    for pos in &shared {
+        let d = [pos[0] - centre[0], pos[1] - centre[1]];
+        spread += (d[0] * d[0] + d[1] * d[1]).sqrt() / n;
+    }
+
+    (centre, spread)
+}
+
+/// Screen angle from the first to the second pointer that is also in `other`.
+/// Counter-clockwise is positive, with y pointing down.
+fn angle(pointers: &[Pointer], other: &[Pointer]) -> Option<f32> {
+    let mut shared = pointers.iter().filter(|p| find(other, p.id).is_some());
+    let a = shared.next()?.pos;
+    let b = shared.next()?.pos;
+    Some((a[1] - b[1]).atan2(b[0] - a[0]))
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointer(id: i32, pos: [f32; 2]) -> Pointer {
+        Pointer { id, pos }
+    }
+
+    fn feed(gestures: &mut Gestures, phase: TouchPhase, pointers: &[Pointer]) -> Vec<Gesture> {
+        let mut out = vec![];
+        gestures.handle(phase, pointers, &mut out);
+        out
+    }
+
+    #[test]
+    fn a_short_touch_is_a_tap() {
+        let mut gestures = Gestures::new();
+        feed(
+            &mut gestures,
+            TouchPhase::Down(0),
+            &[pointer(0, [10.0, 10.0])],
+        );
+        assert!(gestures.active());
+        // Within the slop.
+        let moved = [pointer(0, [20.0, 15.0])];
+        assert!(feed(&mut gestures, TouchPhase::Move, &moved).is_empty());
+        let out = feed(&mut gestures, TouchPhase::Up(0), &moved);
+        assert!(matches!(
+            out[..],
+            [Gesture::Tap {
+                pos: [10.0, 10.0],
+                fingers: 1
+            }]
+        ));
+        assert!(!gestures.active());
+    }
+
+    #[test]
+    fn one_finger_past_the_slop_drags() {
+        let mut gestures = Gestures::new();
+        feed(
+            &mut gestures,
+            TouchPhase::Down(0),
+            &[pointer(0, [10.0, 10.0])],
+        );
+        let out = feed(&mut gestures, TouchPhase::Move, &[pointer(0, [50.0, 10.0])]);
+        assert!(matches!(
+            out[..],
+            [
+                Gesture::DragStart { pos: [10.0, 10.0] },
+                Gesture::Drag {
+                    pos: [50.0, 10.0],
+                    delta: [40.0, 0.0]
+                }
+            ]
+        ));
+        let out = feed(&mut gestures, TouchPhase::Move, &[pointer(0, [55.0, 12.0])]);
+        assert!(matches!(
+            out[..],
+            [Gesture::Drag {
+                delta: [5.0, 2.0],
+                ..
+            }]
+        ));
+        let out = feed(
+            &mut gestures,
+            TouchPhase::Up(0),
+            &[pointer(0, [55.0, 12.0])],
+        );
+        assert!(matches!(out[..], [Gesture::DragEnd]));
+    }
+
+    #[test]
+    fn two_fingers_pinch_instead_of_panning() {
+        let mut gestures = Gestures::new();
+        feed(
+            &mut gestures,
+            TouchPhase::Down(0),
+            &[pointer(0, [100.0, 100.0])],
+        );
+        let both = [pointer(0, [100.0, 100.0]), pointer(1, [200.0, 100.0])];
+        assert!(feed(&mut gestures, TouchPhase::Down(1), &both).is_empty());
+
+        // Spread apart about the same centre.
+        let apart = [pointer(0, [50.0, 100.0]), pointer(1, [250.0, 100.0])];
+        let out = feed(&mut gestures, TouchPhase::Move, &apart);
+        match out[..] {
+            [Gesture::Pinch {
+                focus,
+                delta,
+                scale,
+                rotation,
+            }] => {
+                assert_eq!(focus, [150.0, 100.0]);
+                assert_eq!(delta, [0.0, 0.0]);
+                assert_eq!(scale, 2.0);
+                assert_eq!(rotation, 0.0);
+            }
+            _ => panic!("{:?} isn't one pinch", out),
+        }
+        // Both fingers moving together pan within the pinch.
+        let moved = [pointer(0, [50.0, 130.0]), pointer(1, [250.0, 130.0])];
+        let out = feed(&mut gestures, TouchPhase::Move, &moved);
+        assert!(matches!(
+            out[..],
+            [Gesture::Pinch {
+                delta: [0.0, 30.0],
+                scale,
+                ..
+            }] if scale == 1.0
+        ));
+
+        // Lifting one finger ends the gesture without a tap or a drag.
+        assert!(feed(&mut gestures, TouchPhase::Up(1), &moved).is_empty());
+        let rest = [pointer(0, [90.0, 170.0])];
+        assert!(feed(&mut gestures, TouchPhase::Move, &rest).is_empty());
+        assert!(feed(&mut gestures, TouchPhase::Up(0), &rest).is_empty());
+    }
+
+    #[test]
+    fn a_second_finger_ends_a_drag() {
+        let mut gestures = Gestures::new();
+        feed(
+            &mut gestures,
+            TouchPhase::Down(0),
+            &[pointer(0, [10.0, 10.0])],
+        );
+        feed(&mut gestures, TouchPhase::Move, &[pointer(0, [60.0, 10.0])]);
+        let both = [pointer(0, [60.0, 10.0]), pointer(1, [160.0, 10.0])];
+        let out = feed(&mut gestures, TouchPhase::Down(1), &both);
+        assert!(matches!(out[..], [Gesture::DragEnd]));
+        let apart = [pointer(0, [60.0, 10.0]), pointer(1, [260.0, 10.0])];
+        let out = feed(&mut gestures, TouchPhase::Move, &apart);
+        assert!(matches!(out[..], [Gesture::Pinch { .. }]));
+    }
+
+    #[test]
+    fn two_finger_taps_count_their_fingers() {
+        let mut gestures = Gestures::new();
+        let both = [pointer(0, [10.0, 10.0]), pointer(1, [40.0, 10.0])];
+        feed(&mut gestures, TouchPhase::Down(0), &both[..1]);
+        feed(&mut gestures, TouchPhase::Down(1), &both);
+        assert!(feed(&mut gestures, TouchPhase::Up(1), &both).is_empty());
+        let out = feed(&mut gestures, TouchPhase::Up(0), &both[..1]);
+        assert!(matches!(out[..], [Gesture::Tap { fingers: 2, .. }]));
+    }
+
+    #[test]
+    fn cancelling_ends_a_drag() {
+        let mut gestures = Gestures::new();
+        feed(
+            &mut gestures,
+            TouchPhase::Down(0),
+            &[pointer(0, [10.0, 10.0])],
+        );
+        feed(&mut gestures, TouchPhase::Move, &[pointer(0, [60.0, 10.0])]);
+        let out = feed(&mut gestures, TouchPhase::Cancel, &[]);
+        assert!(matches!(out[..], [Gesture::DragEnd]));
+        assert!(!gestures.active());
+    }
+}
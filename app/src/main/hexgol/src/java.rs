@@ -0,0 +1,119 @@
+//! Calls into `MainActivity` for what the NDK doesn't expose.
+
+use crate::ffi::GameActivity;
+use jni::objects::{JObject, JString, JValue};
+use jni::JavaVM;
+use std::path::Path;
+
+/// Runs `f` with the activity's JNI environment, logging any error. An
+/// exception `f` leaves behind is described and cleared, since no other JNI
+/// call is allowed while one is pending.
+unsafe fn with_activity<T>(
+    activity: *mut GameActivity,
+    f: impl FnOnce(&jni::JNIEnv, JObject) -> jni::errors::Result<T>,
+) -> Option<T> {
+    let vm = match JavaVM::from_raw((*activity).vm as *mut jni::sys::JavaVM) {
+        Ok(vm) => vm,
+        Err(err) => {
+            log::warn!("JNI call failed: {}", err);
+            return None;
+        }
+    };
+    let env = match vm.attach_current_thread() {
+        Ok(env) => env,
+        Err(err) => {
+            log::warn!("JNI call failed: {}", err);
+            return None;
+        }
+    };
+    let result = f(
+        &env,
+        JObject::from_raw((*activity).javaGameActivity as jni::sys::jobject),
+    );
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_describe();
+                let _ = env.exception_clear();
+            }
+            log::warn!("JNI call failed: {}", err);
+            None
+        }
+    }
+}
+
+/// Window edges covered by system bars and display cutouts, as left, top,
+/// right and bottom pixels.
+pub unsafe fn window_insets(activity: *mut GameActivity) -> Option<[i32; 4]> {
+    with_activity(activity, |env, activity| {
+        let array = env
+            .call_method(activity, "windowInsets", "()[I", &[])
+            .and_then(JValue::l)?;
+        let mut insets = [0; 4];
+        env.get_int_array_region(array.into_raw(), 0, &mut insets)?;
+        Ok(insets)
+    })
+}
+
+/// Tells the player about a failure the app can't recover from.
+pub unsafe fn show_error(activity: *mut GameActivity, message: &str) {
+    with_activity(activity, |env, activity| {
+        let message = env.new_string(message)?;
+        env.call_method(
+            activity,
+            "showError",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(message.into())],
+        )?;
+        Ok(())
+    });
+}
+
+/// Offers a file from the export directory to other apps through the share
+/// sheet.
+pub unsafe fn share_file(activity: *mut GameActivity, path: &Path, mime_type: &str) {
+    with_activity(activity, |env, activity| {
+        let path = env.new_string(path.to_string_lossy())?;
+        let mime_type = env.new_string(mime_type)?;
+        env.call_method(
+            activity,
+            "shareFile",
+            "(Ljava/lang/String;Ljava/lang/String;)V",
+            &[
+                JValue::Object(path.into()),
+                JValue::Object(mime_type.into()),
+            ],
+        )?;
+        Ok(())
+    });
+}
+
+/// Offers text to other apps through the share sheet.
+pub unsafe fn share_text(activity: *mut GameActivity, text: &str) {
+    with_activity(activity, |env, activity| {
+        let text = env.new_string(text)?;
+        env.call_method(
+            activity,
+            "shareText",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(text.into())],
+        )?;
+        Ok(())
+    });
+}
+
+/// The text of a file another app opened the game with, if it hasn't been
+/// taken yet.
+pub unsafe fn take_opened_file(activity: *mut GameActivity) -> Option<String> {
+    with_activity(activity, |env, activity| {
+        let text = env
+            .call_method(activity, "takeOpenedFile", "()Ljava/lang/String;", &[])
+            .and_then(JValue::l)?;
+        if text.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(env.get_string(JString::from(text))?.into()))
+    })
+    .flatten()
+}
@@ -0,0 +1,54 @@
+//! The soft keyboard, through GameActivity's text input.
+
+use crate::ffi::*;
+use std::ffi::{c_void, CString};
+
+/// Shows the soft keyboard editing `text`, with the cursor at the end.
+pub unsafe fn show(activity: *mut GameActivity, text: &str) {
+    let text = CString::new(text).unwrap_or_default();
+    let end = text.as_bytes().len() as i32;
+    let state = GameTextInputState {
+        text_UTF8: text.as_ptr(),
+        text_length: end,
+        selection: GameTextInputSpan { start: end, end },
+        composingRegion: GameTextInputSpan {
+            start: GameTextInputSpanFlag_SPAN_UNDEFINED,
+            end: GameTextInputSpanFlag_SPAN_UNDEFINED,
+        },
+    };
+    // The state is copied before this returns.
+    GameActivity_setTextInputState(activity, &state);
+    GameActivity_showSoftInput(activity, 0);
+}
+
+pub unsafe fn hide(activity: *mut GameActivity) {
+    GameActivity_hideSoftInput(activity, 0);
+}
+
+/// The text being edited, if the keyboard changed it since the last call.
+pub unsafe fn take_text(app: *mut android_app) -> Option<String> {
+    let glue = &mut *glue::glue(app);
+    if glue.textInputState == 0 {
+        return None;
+    }
+    // Cleared first, so an edit landing while this reads is picked up next time.
+    glue.textInputState = 0;
+
+    let mut text = String::new();
+    GameActivity_getTextInputState(
+        glue.activity,
+        Some(copy_text),
+        &mut text as *mut String as *mut c_void,
+    );
+    Some(text)
+}
+
+unsafe extern "C" fn copy_text(context: *mut c_void, state: *const GameTextInputState) {
+    let (text, state) = (&mut *(context as *mut String), &*state);
+    if state.text_UTF8.is_null() {
+        return;
+    }
+    let bytes =
+        std::slice::from_raw_parts(state.text_UTF8.cast::<u8>(), state.text_length as usize);
+    *text = String::from_utf8_lossy(bytes).into_owned();
+}
@@ -0,0 +1,77 @@
+struct Life {
+    // Rule bitmasks over neighbour counts.
+    birth: u32,
+    survive: u32,
+    // Radius of the board in cells.
+    size: i32,
+    // Cells per row of the bitmap, 2 * size + 1.
+    width: i32,
+    // Whether the diagonal cells count as neighbours too.
+    extended: u32,
+    // Whether the edges wrap around.
+    wrapped: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> life: Life;
+// One bit per cell of the (2 * size + 1)² square of axial coordinates.
+@group(0) @binding(1)
+var<storage, read> src: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> dst: array<u32>;
+
+fn on_board(q: i32, r: i32) -> bool {
+    return max(max(abs(q), abs(r)), abs(q + r)) <= life.size;
+}
+
+fn alive(q: i32, r: i32) -> u32 {
+    var hex = vec2<i32>(q, r);
+    if (!on_board(q, r)) {
+        if (life.wrapped == 0u) {
+            return 0u;
+        }
+        // Copies of the board are centred on the rotations of this hex.
+        var center = vec2<i32>(2 * life.size + 1, -life.size);
+        for (var i = 0; i < 6; i = i + 1) {
+            if (on_board(q - center.x, r - center.y)) {
+                hex = vec2<i32>(q - center.x, r - center.y);
+                break;
+            }
+            center = vec2<i32>(-center.y, center.x + center.y);
+        }
+    }
+    let index = u32((hex.y + life.size) * life.width + hex.x + life.size);
+    return (src[index / 32u] >> (index % 32u)) & 1u;
+}
+
+// Each invocation steps the 32 cells of one bitmap word.
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let word = id.x;
+    if (word >= arrayLength(&dst)) {
+        return;
+    }
+
+    var next = 0u;
+    for (var bit = 0u; bit < 32u; bit = bit + 1u) {
+        let index = i32(word * 32u + bit);
+        let q = index % life.width - life.size;
+        let r = index / life.width - life.size;
+        if (index >= life.width * life.width || !on_board(q, r)) {
+            continue;
+        }
+
+        var neighbors = alive(q + 1, r) + alive(q, r + 1) + alive(q - 1, r)
+            + alive(q, r - 1) + alive(q + 1, r - 1) + alive(q - 1, r + 1);
+        if (life.extended != 0u) {
+            neighbors = neighbors + alive(q + 2, r - 1) + alive(q + 1, r + 1) + alive(q - 1, r + 2)
+                + alive(q - 2, r + 1) + alive(q - 1, r - 1) + alive(q + 1, r - 2);
+        }
+        var mask = life.birth;
+        if (alive(q, r) == 1u) {
+            mask = life.survive;
+        }
+        next = next | (((mask >> neighbors) & 1u) << bit);
+    }
+    dst[word] = next;
+}
@@ -0,0 +1,451 @@
+use crate::game::{ParseRuleError, Rule};
+use crate::hud::Insets;
+use crate::renderer::{text_size, Instance, Label};
+
+/// Window pixels per font pixel.
+const SCALE: f32 = 3.0;
+/// Side of a button, in pixels.
+const BUTTON: f32 = 72.0;
+const GAP: f32 = 12.0;
+/// Space between the edge of the panel and its contents.
+const PADDING: f32 = 24.0;
+const MAX_WIDTH: f32 = 720.0;
+/// Smallest the panel shrinks to in a short window, as a share of its size.
+const MIN_FIT: f32 = 0.5;
+
+const SHADE_ALPHA: f32 = 0.6;
+const PANEL_COLOR: [f32; 3] = [0.05, 0.05, 0.05];
+const BUTTON_COLOR: [f32; 3] = [0.12, 0.12, 0.12];
+const TEXT_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+const NOTE_COLOR: [f32; 3] = [0.5, 0.5, 0.5];
+const ERROR_COLOR: [f32; 3] = [1.0, 0.35, 0.3];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Setting {
+    Rule,
+    Neighborhood,
+    Topology,
+    Speed,
+    /// What to do once the board settles into a cycle.
+    OnCycle,
+    Theme,
+    /// Cells, heatmap or trails.
+    View,
+    /// Whether births and deaths are animated.
+    Animation,
+    Grid,
+    Graph,
+    BoardSize,
+    History,
+    Backend,
+}
+impl Setting {
+    /// Rows of the panel, top to bottom.
+    pub const ALL: [Self; 13] = [
+        Self::Rule,
+        Self::Neighborhood,
+        Self::Topology,
+        Self::Speed,
+        Self::OnCycle,
+        Self::Theme,
+        Self::View,
+        Self::Animation,
+        Self::Grid,
+        Self::Graph,
+        Self::BoardSize,
+        Self::History,
+        Self::Backend,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Rule => "Rule",
+            Self::Neighborhood => "Neighbours",
+            Self::Topology => "Edges",
+            Self::Speed => "Speed",
+            Self::OnCycle => "On cycle",
+            Self::Theme => "Theme",
+            Self::View => "View",
+            Self::Animation => "Animation",
+            Self::Grid => "Grid",
+            Self::Graph => "Graph",
+            Self::BoardSize => "Board size",
+            Self::History => "Undo memory",
+            Self::Backend => "Graphics",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PanelHit {
+    Close,
+    /// Step a setting through its options, by -1 or 1.
+    Step(Setting, isize),
+    /// The rule's value, which can be typed in.
+    RuleValue,
+    /// Somewhere on the panel without a button.
+    Panel,
+}
+
+/// A rule being typed on the soft keyboard.
+pub struct RuleEntry {
+    pub text: String,
+}
+impl RuleEntry {
+    pub fn rule(&self) -> Result<Rule, ParseRuleError> {
+        self.text.parse()
+    }
+}
+
+/// A dialog in the middle of the window listing the settings, each with
+/// buttons to step through its options. Tapping outside it closes it.
+pub struct SettingsPanel {
+    pub open: bool,
+    /// Replaces the rule's value while it's being typed.
+    pub entry: Option<RuleEntry>,
+}
+impl Default for SettingsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl SettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            entry: None,
+        }
+    }
+
+    /// What a tap at a window position does, if the panel is open.
+    pub fn hit(&self, pos: [f32; 2], size: [u32; 2], insets: Insets) -> Option<PanelHit> {
+        if !self.open {
+            return None;
+        }
+
+        let layout = Layout::new(size, insets);
+        let button = [layout.button, layout.button];
+        if !contains(layout.corner, layout.extent, pos) {
+            return Some(PanelHit::Close);
+        }
+        if contains(layout.close_pos(), button, pos) {
+            return Some(PanelHit::Close);
+        }
+        let rule_row = Setting::ALL
+            .iter()
+            .position(|setting| *setting == Setting::Rule)
+            .unwrap_or(0);
+        let value_left = layout.corner[0] + layout.padding;
+        let value_top = layout.row_top(rule_row + 1);
+        let value_width = layout.button_pos(rule_row, -1)[0] - layout.gap - value_left;
+        if contains([value_left, value_top], [value_width, layout.button], pos) {
+            return Some(PanelHit::RuleValue);
+        }
+        for (row, setting) in Setting::ALL.into_iter().enumerate() {
+            if contains(layout.button_pos(row, -1), button, pos) {
+                return Some(PanelHit::Step(setting, -1));
+            }
+            if contains(layout.button_pos(row, 1), button, pos) {
+                return Some(PanelHit::Step(setting, 1));
+            }
+        }
+        Some(PanelHit::Panel)
+    }
+
+    /// Quads for the overlay and the text on them. `values` lines up with
+    /// `Setting::ALL`; `note` goes under the rows.
+    pub fn instances(
+        &self,
+        values: &[String],
+        note: Option<&str>,
+        size: [u32; 2],
+        insets: Insets,
+    ) -> (Vec<Instance>, Vec<Label>) {
+        let mut quads = vec![];
+        let mut labels = vec![];
+        if !self.open {
+            return (quads, labels);
+        }
+
+        // Shade the board so the panel stands out.
+        quads.push(
+            Instance::new([0.0, 0.0], [size[0] as f32, size[1] as f32], [0.0; 3])
+                .with_alpha(SHADE_ALPHA),
+        );
+        let layout = Layout::new(size, insets);
+        quads.push(Instance::new(layout.corner, layout.extent, PANEL_COLOR));
+
+        let label = |text: &str, pos: [f32; 2], color: [f32; 3]| Label {
+            text: text.to_string(),
+            pos,
+            scale: layout.text_scale,
+            color,
+        };
+        // Text is centred vertically on the buttons in its row.
+        let text_top =
+            |row: usize| layout.row_top(row) + (layout.button - layout.line_height()) / 2.0;
+        let left = layout.corner[0] + layout.padding;
+
+        labels.push(label("Settings", [left, text_top(0)], TEXT_COLOR));
+        layout.button(layout.close_pos(), "X", &mut quads, &mut labels);
+
+        for (row, (setting, value)) in Setting::ALL.iter().zip(values).enumerate() {
+            let row = row + 1;
+            labels.push(label(setting.label(), [left, text_top(row)], TEXT_COLOR));
+
+            // The rule being typed shows with a cursor, in red until it parses.
+            let (value, color) = match &self.entry {
+                Some(entry) if *setting == Setting::Rule => {
+                    let color = match entry.rule() {
+                        Ok(_) => TEXT_COLOR,
+                        Err(_) => ERROR_COLOR,
+                    };
+                    (format!("{}_", entry.text), color)
+                }
+                _ => (value.clone(), TEXT_COLOR),
+            };
+            let previous = layout.button_pos(row - 1, -1);
+            let width = text_size(&value, layout.text_scale)[0];
+            labels.push(label(
+                &value,
+                [previous[0] - layout.gap - width, text_top(row)],
+                color,
+            ));
+            layout.button(previous, "<", &mut quads, &mut labels);
+            layout.button(layout.button_pos(row - 1, 1), ">", &mut quads, &mut labels);
+        }
+
+        // While typing, the note says what's wrong with the rule, if anything.
+        let note = match &self.entry {
+            Some(entry) => Some(match entry.rule() {
+                Ok(_) => ("Enter applies the rule".to_string(), NOTE_COLOR),
+                Err(err) => (err.to_string(), ERROR_COLOR),
+            }),
+            None => note.map(|note| (note.to_string(), NOTE_COLOR)),
+        };
+        if let Some((note, color)) = note {
+            labels.push(label(
+                &note,
+                [left, layout.row_top(Setting::ALL.len() + 1)],
+                color,
+            ));
+        }
+
+        (quads, labels)
+    }
+}
+
+/// Where the panel goes in a window: centred in the part the insets leave
+/// clear, and shrunk to fit it if the window is short.
+struct Layout {
+    corner: [f32; 2],
+    extent: [f32; 2],
+    button: f32,
+    gap: f32,
+    padding: f32,
+    text_scale: f32,
+}
+impl Layout {
+    fn new(size: [u32; 2], insets: Insets) -> Self {
+        let clear = [
+            size[0] as f32 - insets.left - insets.right - 2.0 * GAP,
+            size[1] as f32 - insets.top - insets.bottom - 2.0 * GAP,
+        ];
+        let rows = Setting::ALL.len() + 1;
+        let height = 2.0 * PADDING + rows as f32 * (BUTTON + GAP) + text_size("", SCALE)[1];
+        let fit = (clear[1] / height).clamp(MIN_FIT, 1.0);
+
+        let extent = [MAX_WIDTH.min(clear[0]), height * fit];
+        let corner = [
+            insets.left + GAP + (clear[0] - extent[0]) / 2.0,
+            insets.top + GAP + (clear[1] - extent[1]).max(0.0) / 2.0,
+        ];
+        Self {
+            corner,
+            extent,
+            button: BUTTON * fit,
+            gap: GAP * fit,
+            padding: PADDING * fit,
+            text_scale: SCALE * fit,
+        }
+    }
+
+    fn line_height(&self) -> f32 {
+        text_size("", self.text_scale)[1]
+    }
+
+    /// Top of a row, counting the title as row 0.
+    fn row_top(&self, row: usize) -> f32 {
+        self.corner[1] + self.padding + row as f32 * (self.button + self.gap)
+    }
+
+    fn close_pos(&self) -> [f32; 2] {
+        [self.right(), self.row_top(0)]
+    }
+
+    /// Top left corner of the button stepping a setting by `step`.
+    fn button_pos(&self, setting: usize, step: isize) -> [f32; 2] {
+        let x = if step < 0 {
+            self.right() - self.gap - self.button
+        } else {
+            self.right()
+        };
+        [x, self.row_top(setting + 1)]
+    }
+
+    /// Left of the buttons in the right-hand column.
+    fn right(&self) -> f32 {
+        self.corner[0] + self.extent[0] - self.padding - self.button
+    }
+
+    fn button(
+        &self,
+        pos: [f32; 2],
+        text: &str,
+        quads: &mut Vec<Instance>,
+        labels: &mut Vec<Label>,
+    ) {
+        quads.push(Instance::new(pos, [self.button, self.button], BUTTON_COLOR));
+        let extent = text_size(text, self.text_scale);
+        labels.push(Label {
+            text: text.to_string(),
+            pos: [
+                pos[0] + (self.button - extent[0]) / 2.0,
+                pos[1] + (self.button - extent[1]) / 2.0,
+            ],
+            scale: self.text_scale,
+            color: TEXT_COLOR,
+        });
+    }
+}
+
+fn contains(corner: [f32; 2], extent: [f32; 2], pos: [f32; 2]) -> bool {
+    pos[0] >= corner[0]
+        && pos[0] < corner[0] + extent[0]
+        && pos[1] >= corner[1]
+        && pos[1] < corner[1] + extent[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHONE: [u32; 2] = [1080, 2340];
+
+    fn open() -> SettingsPanel {
+        SettingsPanel {
+            open: true,
+            entry: None,
+        }
+    }
+
+    fn values() -> Vec<String> {
+        Setting::ALL
+            .iter()
+            .map(|setting| format!("{:?}", setting))
+            .collect()
+    }
+
+    /// A point inside a label, which buttons centre theirs on.
+    fn inside(label: &Label) -> [f32; 2] {
+        let [width, height] = text_size(&label.text, label.scale);
+        [label.pos[0] + width / 2.0, label.pos[1] + height / 2.0]
+    }
+
+    fn check_buttons(size: [u32; 2], insets: Insets) {
+        let panel = open();
+        let (_quads, labels) = panel.instances(&values(), None, size, insets);
+        let find = |text: &'static str| labels.iter().filter(move |label| label.text == text);
+
+        let close = find("X").next().unwrap();
+        assert_eq!(
+            panel.hit(inside(close), size, insets),
+            Some(PanelHit::Close)
+        );
+        // Buttons come a row at a time, in the order of the settings.
+        let previous: Vec<&Label> = find("<").collect();
+        let next: Vec<&Label> = find(">").collect();
+        assert_eq!(previous.len(), Setting::ALL.len());
+        for (i, setting) in Setting::ALL.into_iter().enumerate() {
+            assert_eq!(
+                panel.hit(inside(previous[i]), size, insets),
+                Some(PanelHit::Step(setting, -1))
+            );
+            assert_eq!(
+                panel.hit(inside(next[i]), size, insets),
+                Some(PanelHit::Step(setting, 1))
+            );
+        }
+
+        let rule = find("Rule").next().unwrap();
+        assert_eq!(
+            panel.hit(inside(rule), size, insets),
+            Some(PanelHit::RuleValue)
+        );
+        let speed = find("Speed").next().unwrap();
+        assert_eq!(
+            panel.hit(inside(speed), size, insets),
+            Some(PanelHit::Panel)
+        );
+    }
+
+    #[test]
+    fn maps_buttons_to_their_settings() {
+        check_buttons(PHONE, Insets::default());
+        check_buttons([800, 600], Insets::default());
+    }
+
+    #[test]
+    fn taps_outside_close_it() {
+        let panel = open();
+        assert_eq!(
+            panel.hit([1.0, 1.0], PHONE, Insets::default()),
+            Some(PanelHit::Close)
+        );
+        assert_eq!(
+            SettingsPanel::new().hit([500.0, 1000.0], PHONE, Insets::default()),
+            None
+        );
+        assert!(SettingsPanel::new()
+            .instances(&values(), None, PHONE, Insets::default())
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn stays_inside_the_insets() {
+        // A landscape phone with a cutout on the left and navigation on the
+        // right.
+        let size = [2340, 1080];
+        let insets = Insets {
+            left: 130.0,
+            top: 60.0,
+            right: 150.0,
+            bottom: 40.0,
+        };
+        let layout = Layout::new(size, insets);
+        assert!(layout.corner[0] >= insets.left && layout.corner[1] >= insets.top);
+        assert!(layout.corner[0] + layout.extent[0] <= size[0] as f32 - insets.right);
+        assert!(layout.corner[1] + layout.extent[1] <= size[1] as f32 - insets.bottom);
+        // Too short for the panel at full size, so it shrinks.
+        assert!(layout.button < BUTTON);
+
+        let (_quads, labels) = open().instances(&values(), Some("Note"), size, insets);
+        for label in &labels {
+            let [width, height] = text_size(&label.text, label.scale);
+            assert!(label.pos[0] >= insets.left && label.pos[1] >= insets.top);
+            assert!(label.pos[0] + width <= size[0] as f32 - insets.right);
+            assert!(label.pos[1] + height <= size[1] as f32 - insets.bottom);
+        }
+        check_buttons(size, insets);
+    }
+
+    #[test]
+    fn keeps_full_size_when_it_fits() {
+        let layout = Layout::new(PHONE, Insets::default());
+        assert_eq!(layout.button, BUTTON);
+        assert_eq!(layout.extent[0], MAX_WIDTH);
+        // Centred.
+        assert_eq!(layout.corner[0], (PHONE[0] as f32 - MAX_WIDTH) / 2.0);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::game::*;
+use crate::renderer::Instance;
+
+/// Side of a picker slot, in pixels.
+const SLOT: f32 = 96.0;
+const MARGIN: f32 = 12.0;
+
+const SLOT_COLOR: [f32; 3] = [0.12, 0.12, 0.12];
+const SELECTED_COLOR: [f32; 3] = [0.2, 0.3, 0.5];
+const ICON_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+
+/// Icon cells, laid out like pattern cells.
+const TOGGLE_ICON: [HexInt; 7] = [
+    HexInt::new(0, 0),
+    HexInt::new(1, 0),
+    HexInt::new(0, 1),
+    HexInt::new(-1, 0),
+    HexInt::new(0, -1),
+    HexInt::new(1, -1),
+    HexInt::new(-1, 1),
+];
+const ROTATE_ICON: [HexInt; 5] = [
+    HexInt::new(1, 0),
+    HexInt::new(0, 1),
+    HexInt::new(-1, 1),
+    HexInt::new(-1, 0),
+    HexInt::new(0, -1),
+];
+const MIRROR_ICON: [HexInt; 6] = [
+    HexInt::new(-1, 0),
+    HexInt::new(-1, 1),
+    HexInt::new(-2, 1),
+    HexInt::new(1, 0),
+    HexInt::new(1, -1),
+    HexInt::new(2, -1),
+];
+const PAINT_ICON: [HexInt; 5] = [
+    HexInt::new(0, 0),
+    HexInt::new(1, 0),
+    HexInt::new(2, 0),
+    HexInt::new(3, -1),
+    HexInt::new(4, -2),
+];
+const SELECT_ICON: [HexInt; 8] = [
+    HexInt::new(0, 0),
+    HexInt::new(1, 0),
+    HexInt::new(2, 0),
+    HexInt::new(0, 1),
+    HexInt::new(2, 1),
+    HexInt::new(0, 2),
+    HexInt::new(1, 2),
+    HexInt::new(2, 2),
+];
+const COPY_ICON: [HexInt; 4] = [
+    HexInt::new(0, 0),
+    HexInt::new(0, 1),
+    HexInt::new(2, 0),
+    HexInt::new(2, 1),
+];
+const CUT_ICON: [HexInt; 5] = [
+    HexInt::new(0, 0),
+    HexInt::new(0, 1),
+    HexInt::new(2, 0),
+    HexInt::new(1, 1),
+    HexInt::new(1, 2),
+];
+const PASTE_ICON: [HexInt; 4] = [
+    HexInt::new(0, 0),
+    HexInt::new(0, 1),
+    HexInt::new(1, 1),
+    HexInt::new(0, 3),
+];
+const CLEAR_ICON: [HexInt; 1] = [HexInt::new(0, 0)];
+const SHARE_ICON: [HexInt; 5] = [
+    HexInt::new(0, 0),
+    HexInt::new(1, -1),
+    HexInt::new(1, 0),
+    HexInt::new(2, -2),
+    HexInt::new(2, 0),
+];
+/// The share arrow over a line of text.
+const SHARE_PATTERN_ICON: [HexInt; 8] = [
+    HexInt::new(0, 0),
+    HexInt::new(1, -1),
+    HexInt::new(1, 0),
+    HexInt::new(2, -2),
+    HexInt::new(2, 0),
+    HexInt::new(-1, 3),
+    HexInt::new(0, 3),
+    HexInt::new(1, 3),
+];
+/// Objects of three kinds, as a census counts them.
+const CENSUS_ICON: [HexInt; 6] = [
+    HexInt::new(0, 0),
+    HexInt::new(3, -1),
+    HexInt::new(3, 0),
+    HexInt::new(0, 2),
+    HexInt::new(1, 2),
+    HexInt::new(0, 3),
+];
+const SETTINGS_ICON: [HexInt; 9] = [
+    HexInt::new(0, 0),
+    HexInt::new(2, -1),
+    HexInt::new(4, -2),
+    HexInt::new(0, 1),
+    HexInt::new(2, 0),
+    HexInt::new(4, -1),
+    HexInt::new(0, 2),
+    HexInt::new(2, 1),
+    HexInt::new(4, 0),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PickerHit {
+    Toggle,
+    Rotate,
+    Mirror,
+    Paint,
+    Select,
+    Copy,
+    Cut,
+    Paste,
+    Clear,
+    /// Share a picture of the board.
+    Share,
+    /// Share the selection, or the whole board, as pattern text.
+    SharePattern,
+    Census,
+    Settings,
+    Pattern(usize),
+}
+
+/// Slots in front of the patterns.
+const TOOLS: [(PickerHit, &[HexInt]); 12] = [
+    (PickerHit::Rotate, &ROTATE_ICON),
+    (PickerHit::Mirror, &MIRROR_ICON),
+    (PickerHit::Paint, &PAINT_ICON),
+    (PickerHit::Select, &SELECT_ICON),
+    (PickerHit::Copy, &COPY_ICON),
+    (PickerHit::Cut, &CUT_ICON),
+    (PickerHit::Paste, &PASTE_ICON),
+    (PickerHit::Clear, &CLEAR_ICON),
+    (PickerHit::Share, &SHARE_ICON),
+    (PickerHit::SharePattern, &SHARE_PATTERN_ICON),
+    (PickerHit::Census, &CENSUS_ICON),
+    (PickerHit::Settings, &SETTINGS_ICON),
+];
+
+/// A button in the top right corner that opens a strip of pattern thumbnails
+/// along the top of the screen, led by the editing tools.
+pub struct Picker {
+    pub open: bool,
+}
+impl Default for Picker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Picker {
+    pub fn new() -> Self {
+        Self { open: false }
+    }
+
+    /// Pixels the toggle button covers from the top of the window.
+    pub fn height() -> f32 {
+        MARGIN + SLOT
+    }
+
+    /// The slot under a window position, if any.
+    pub fn hit(&self, pos: [f32; 2], patterns: usize, size: [u32; 2]) -> Option<PickerHit> {
+        if contains(toggle_pos(size), pos) {
+            return Some(PickerHit::Toggle);
+        }
+        if !self.open {
+            return None;
+        }
+
+        (0..TOOLS.len() + patterns)
+            .find(|&slot| contains(slot_pos(slot, size), pos))
+            .map(|slot| match TOOLS.get(slot) {
+                Some((tool, _icon)) => *tool,
+                None => PickerHit::Pattern(slot - TOOLS.len()),
+            })
+    }
+
+    /// Quads and hexagons in window pixels for the overlay. Tools paired with
+    /// `true` in `modes` show as switched on. Thumbnails are shown in the
+    /// orientation a stamp would be placed in.
+    pub fn instances(
+        &self,
+        patterns: &[Pattern],
+        selected: Option<usize>,
+        modes: &[(PickerHit, bool)],
+        rotations: u8,
+        mirror: bool,
+        size: [u32; 2],
+    ) -> (Vec<Instance>, Vec<Instance>) {
+        let mut quads = vec![];
+        let mut hexes = vec![];
+
+        let toggle = toggle_pos(size);
+        let toggle_color = if self.open || selected.is_some() {
+            SELECTED_COLOR
+        } else {
+            SLOT_COLOR
+        };
+        quads.push(Instance::new(toggle, [SLOT, SLOT], toggle_color));
+        icon(&TOGGLE_ICON, toggle, ICON_COLOR, &mut hexes);
+
+        if !self.open {
+            return (quads, hexes);
+        }
+
+        for slot in 0..TOOLS.len() + patterns.len() {
+            let pos = slot_pos(slot, size);
+            let (active, cells) = match TOOLS.get(slot) {
+                Some((tool, icon)) => {
+                    let active = modes.iter().any(|(mode, on)| mode == tool && *on);
+                    (active, icon.to_vec())
+                }
+                None => {
+                    let pattern = slot - TOOLS.len();
+                    (
+                        selected == Some(pattern),
+                        patterns[pattern].transformed(rotations, mirror),
+                    )
+                }
+            };
+            let color = if active { SELECTED_COLOR } else { SLOT_COLOR };
+            quads.push(Instance::new(pos, [SLOT, SLOT], color));
+            icon(&cells, pos, ICON_COLOR, &mut hexes);
+        }
+
+        (quads, hexes)
+    }
+}
+
+fn toggle_pos(size: [u32; 2]) -> [f32; 2] {
+    [size[0] as f32 - MARGIN - SLOT, MARGIN]
+}
+
+/// Top left corner of a slot. Slots fill rows left of the toggle button.
+fn slot_pos(slot: usize, size: [u32; 2]) -> [f32; 2] {
+    let width = size[0] as f32 - SLOT - 3.0 * MARGIN;
+    let columns = ((width / (SLOT + MARGIN)) as usize).max(1);
+    [
+        MARGIN + (slot % columns) as f32 * (SLOT + MARGIN),
+        MARGIN + (slot / columns) as f32 * (SLOT + MARGIN),
+    ]
+}
+
+fn contains(corner: [f32; 2], pos: [f32; 2]) -> bool {
+    pos[0] >= corner[0]
+        && pos[0] < corner[0] + SLOT
+        && pos[1] >= corner[1]
+        && pos[1] < corner[1] + SLOT
+}
+
+/// Fits cells into a slot, flipping y since window pixels point down.
+fn icon(cells: &[HexInt], corner: [f32; 2], color: [f32; 3], out: &mut Vec<Instance>) {
+    let points: Vec<[f32; 2]> = cells
+        .iter()
+        .map(|hex| HexFract::from(*hex).transform(1.0))
+        .collect();
+
+    let extent = |axis: usize| {
+        let min = points.iter().map(|p| p[axis]).fold(f32::MAX, f32::min);
+        let max = points.iter().map(|p| p[axis]).fold(f32::MIN, f32::max);
+        (min, max)
+    };
+    let (min_x, max_x) = extent(0);
+    let (min_y, max_y) = extent(1);
+
+    // Each hexagon reaches one unit past its centre.
+    let span = (max_x - min_x).max(max_y - min_y) + 2.0;
+    let scale = SLOT * 0.8 / span;
+    let centre = [(min_x + max_x) / 2.0, (min_y + max_y) / 2.0];
+
+    for point in &points {
+        out.push(Instance::new(
+            [
+                corner[0] + SLOT / 2.0 + (point[0] - centre[0]) * scale,
+                corner[1] + SLOT / 2.0 - (point[1] - centre[1]) * scale,
+            ],
+            [scale * 0.9, scale * 0.9],
+            color,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: [u32; 2] = [1080, 2340];
+
+    fn centre(corner: [f32; 2]) -> [f32; 2] {
+        [corner[0] + SLOT / 2.0, corner[1] + SLOT / 2.0]
+    }
+
+    #[test]
+    fn only_the_toggle_shows_while_closed() {
+        let picker = Picker::new();
+        let toggle = centre(toggle_pos(SIZE));
+        assert_eq!(picker.hit(toggle, 3, SIZE), Some(PickerHit::Toggle));
+        assert_eq!(picker.hit(centre(slot_pos(0, SIZE)), 3, SIZE), None);
+        // The toggle sits in the top right corner, below the margin.
+        assert_eq!(toggle_pos(SIZE), [1080.0 - MARGIN - SLOT, MARGIN]);
+        assert_eq!(Picker::height(), MARGIN + SLOT);
+    }
+
+    #[test]
+    fn slots_hold_the_tools_then_the_patterns() {
+        let picker = Picker { open: true };
+        let patterns = 3;
+        for (slot, (tool, _icon)) in TOOLS.iter().enumerate() {
+            assert_eq!(
+                picker.hit(centre(slot_pos(slot, SIZE)), patterns, SIZE),
+                Some(*tool)
+            );
+        }
+        for pattern in 0..patterns {
+            let pos = centre(slot_pos(TOOLS.len() + pattern, SIZE));
+            assert_eq!(
+                picker.hit(pos, patterns, SIZE),
+                Some(PickerHit::Pattern(pattern))
+            );
+        }
+        // No slot past the last pattern.
+        let past = centre(slot_pos(TOOLS.len() + patterns, SIZE));
+        assert_eq!(picker.hit(past, patterns, SIZE), None);
+    }
+
+    #[test]
+    fn slots_fill_rows_left_of_the_toggle() {
+        // (1080 - 96 - 36) / 108 leaves room for 8 columns.
+        assert_eq!(slot_pos(0, SIZE), [MARGIN, MARGIN]);
+        assert_eq!(slot_pos(7, SIZE), [MARGIN + 7.0 * (SLOT + MARGIN), MARGIN]);
+        assert_eq!(slot_pos(8, SIZE), [MARGIN, MARGIN + SLOT + MARGIN]);
+        let last = slot_pos(7, SIZE);
+        assert!(last[0] + SLOT <= toggle_pos(SIZE)[0]);
+
+        // A window too narrow for a row still gets a column.
+        assert_eq!(slot_pos(1, [100, 800]), [MARGIN, MARGIN + SLOT + MARGIN]);
+    }
+
+    #[test]
+    fn gaps_between_slots_miss() {
+        let picker = Picker { open: true };
+        let gap = [MARGIN + SLOT + MARGIN / 2.0, MARGIN + SLOT / 2.0];
+        assert_eq!(picker.hit(gap, 0, SIZE), None);
+    }
+
+    #[test]
+    fn draws_a_slot_for_each_tool_and_pattern() {
+        let picker = Picker { open: true };
+        let patterns = vec![Pattern::new(
+            "Dot".to_string(),
+            None,
+            vec![HexInt::new(0, 0)],
+        )];
+        let (quads, hexes) = picker.instances(&patterns, None, &[], 0, false, SIZE);
+        // The toggle and every slot.
+        assert_eq!(quads.len(), 1 + TOOLS.len() + 1);
+        let icons: usize = TOOLS.iter().map(|(_tool, icon)| icon.len()).sum();
+        assert_eq!(hexes.len(), TOGGLE_ICON.len() + icons + 1);
+    }
+}
@@ -0,0 +1,539 @@
+use super::*;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct BoardUniform {
+    live: [f32; 4],
+    dying: [f32; 4],
+    size: i32,
+    width: i32,
+    /// Fraction of the tick since the last generation.
+    progress: f32,
+    /// Whether births and deaths are animated.
+    animate: u32,
+}
+
+impl BoardUniform {
+    fn new(size: i32, live: [f32; 3], dying: [f32; 3]) -> Self {
+        Self {
+            live: [live[0], live[1], live[2], 1.0],
+            dying: [dying[0], dying[1], dying[2], 1.0],
+            size,
+            width: 2 * size + 1,
+            progress: 1.0,
+            animate: 0,
+        }
+    }
+}
+
+/// Axial coordinates of one cell, the per-instance input of the board shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CellInstance {
+    hex: [i32; 2],
+}
+impl CellInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![1 => Sint32x2];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Bitmap words per texture row. A row is then 256 bytes, the alignment
+/// texture copies need.
+const TEXTURE_WIDTH: u32 = 64;
+
+/// Where the board's two bitmaps live on the GPU.
+enum Bitmaps {
+    /// Storage buffers, which `GpuLife` can step.
+    Buffers([wgpu::Buffer; 2]),
+    /// Integer textures of `TEXTURE_WIDTH` words per row, for devices whose
+    /// vertex shaders can't read storage buffers.
+    Textures([wgpu::Texture; 2]),
+}
+impl Bitmaps {
+    fn new(gfx: &Graphics, words: usize) -> Self {
+        let device = &gfx.context().device;
+        if gfx.compute() {
+            return Self::Buffers([0, 1].map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Board Bitmap Buffer"),
+                    size: (words * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::STORAGE
+                        | wgpu::BufferUsages::COPY_DST
+                        | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                })
+            }));
+        }
+        Self::Textures([0, 1].map(|_| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Board Bitmap Texture"),
+                size: Self::extent(words),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R32Uint,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+            })
+        }))
+    }
+
+    fn extent(words: usize) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: TEXTURE_WIDTH,
+            height: words as u32 / TEXTURE_WIDTH,
+            depth_or_array_layers: 1,
+        }
+    }
+    /// Layout of whole rows of words, in textures and in buffers copied to and
+    /// from them.
+    fn layout() -> wgpu::ImageDataLayout {
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(
+                TEXTURE_WIDTH * std::mem::size_of::<u32>() as u32,
+            ),
+            rows_per_image: None,
+        }
+    }
+
+    fn binding_type(&self) -> wgpu::BindingType {
+        match self {
+            Self::Buffers(_) => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            Self::Textures(_) => wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Uint,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+        }
+    }
+    /// Binds the uniform, then bitmap `front` to draw and the other one as the
+    /// previous generation.
+    fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        front: usize,
+    ) -> wgpu::BindGroup {
+        let views;
+        let [cells, previous] = match self {
+            Self::Buffers(buffers) => [front, 1 - front].map(|i| buffers[i].as_entire_binding()),
+            Self::Textures(textures) => {
+                views = [front, 1 - front]
+                    .map(|i| textures[i].create_view(&wgpu::TextureViewDescriptor::default()));
+                [
+                    wgpu::BindingResource::TextureView(&views[0]),
+                    wgpu::BindingResource::TextureView(&views[1]),
+                ]
+            }
+        };
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: cells,
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: previous,
+                },
+            ],
+            label: Some("board_bind_group"),
+        })
+    }
+    /// The board shader, after the bindings and functions that read the
+    /// bitmaps from wherever they are. Functions have to come before their
+    /// callers.
+    fn shader(&self) -> wgpu::ShaderModuleDescriptor<'static> {
+        let source = match self {
+            Self::Buffers(_) => concat!(
+                include_str!("../board_buffers.wgsl"),
+                include_str!("../board.wgsl")
+            ),
+            Self::Textures(_) => concat!(
+                include_str!("../board_textures.wgsl"),
+                include_str!("../board.wgsl")
+            ),
+        };
+        wgpu::ShaderModuleDescriptor {
+            label: Some("board.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        }
+    }
+
+    fn write(&self, ctx: &WgpuContext, index: usize, words: &[u32]) {
+        match self {
+            Self::Buffers(buffers) => {
+                ctx.queue
+                    .write_buffer(&buffers[index], 0, bytemuck::cast_slice(words))
+            }
+            Self::Textures(textures) => ctx.queue.write_texture(
+                textures[index].as_image_copy(),
+                bytemuck::cast_slice(words),
+                Self::layout(),
+                Self::extent(words.len()),
+            ),
+        }
+    }
+    fn copy_to(&self, encoder: &mut wgpu::CommandEncoder, index: usize, staging: &wgpu::Buffer) {
+        match self {
+            Self::Buffers(buffers) => {
+                encoder.copy_buffer_to_buffer(&buffers[index], 0, staging, 0, staging.size())
+            }
+            Self::Textures(textures) => encoder.copy_texture_to_buffer(
+                textures[index].as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: staging,
+                    layout: Self::layout(),
+                },
+                Self::extent(staging.size() as usize / std::mem::size_of::<u32>()),
+            ),
+        }
+    }
+}
+
+/// Every cell of a hexagonal board as one static instanced mesh. Only a bitmap
+/// of cell states is uploaded when the board changes; the shader looks each
+/// cell up by its axial coordinates and skips the dead ones.
+///
+/// There are two bitmaps so `GpuLife` can step from one into the other. The
+/// front one is drawn; cells alive only in the back one, the previous
+/// generation, are drawn as dying while deaths are animated. They're storage
+/// buffers where `GpuLife` can run and textures elsewhere.
+pub struct Board {
+    render_pipeline: wgpu::RenderPipeline,
+    hexagon: Mesh,
+    cell_buffer: wgpu::Buffer,
+    num_cells: u32,
+    size: i32,
+    /// One bit per cell of the `(2 * size + 1)²` square around the board,
+    /// padded to whole texture rows.
+    bitmap: Vec<u32>,
+    bitmaps: Bitmaps,
+    bind_groups: [wgpu::BindGroup; 2],
+    uniform: BoardUniform,
+    uniform_buffer: wgpu::Buffer,
+    front: usize,
+}
+impl Board {
+    pub fn new(gfx: &Graphics, size: i32, live: [f32; 3], dying: [f32; 3]) -> Self {
+        let device = &gfx.context().device;
+
+        let mut cells = vec![];
+        for q in -size..=size {
+            for r in (-size).max(-q - size)..=size.min(-q + size) {
+                cells.push(CellInstance { hex: [q, r] });
+            }
+        }
+        let cell_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Board Cell Buffer"),
+            contents: bytemuck::cast_slice(&cells),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let width = 2 * size + 1;
+        let words = ((width * width) as usize)
+            .div_ceil(32)
+            .next_multiple_of(TEXTURE_WIDTH as usize);
+        let bitmap = vec![0; words];
+        let bitmaps = Bitmaps::new(gfx, words);
+        bitmaps.write(gfx.context(), 0, &bitmap);
+        bitmaps.write(gfx.context(), 1, &bitmap);
+        let uniform = BoardUniform::new(size, live, dying);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Board Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bitmap_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: bitmaps.binding_type(),
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                bitmap_entry(1),
+                bitmap_entry(2),
+            ],
+            label: Some("Board bind group"),
+        });
+        let bind_groups =
+            [0, 1].map(|i| bitmaps.bind_group(device, &bind_group_layout, &uniform_buffer, i));
+
+        let shader = device.create_shader_module(bitmaps.shader());
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Board Pipeline Layout"),
+                bind_group_layouts: &[&gfx.camera_bind_group_layout, &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Board Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), CellInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gfx.format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: gfx.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            hexagon: MeshBuilder::new_hexagon([0.0, 0.0], 1.0).build(gfx.context()),
+            cell_buffer,
+            num_cells: cells.len() as u32,
+            size,
+            bitmap,
+            bitmaps,
+            bind_groups,
+            uniform,
+            uniform_buffer,
+            front: 0,
+        }
+    }
+
+    pub fn set_colors(&mut self, ctx: &WgpuContext, live: [f32; 3], dying: [f32; 3]) {
+        self.uniform = BoardUniform {
+            animate: self.uniform.animate,
+            progress: self.uniform.progress,
+            ..BoardUniform::new(self.size, live, dying)
+        };
+        self.write_uniform(ctx);
+    }
+    /// Sets how far into the tick the board is drawn, or `None` to draw each
+    /// generation as is.
+    pub fn set_progress(&mut self, ctx: &WgpuContext, progress: Option<f32>) {
+        self.uniform.animate = progress.is_some() as u32;
+        self.uniform.progress = progress.unwrap_or(1.0);
+        self.write_uniform(ctx);
+    }
+    fn write_uniform(&self, ctx: &WgpuContext) {
+        ctx.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[self.uniform]),
+        );
+    }
+
+    /// Uploads the next generation, keeping the current one to show the cells
+    /// that die.
+    pub fn advance(&mut self, ctx: &WgpuContext, live: impl Iterator<Item = [i32; 2]>) {
+        self.swap();
+        self.update(ctx, live);
+    }
+    /// Uploads the live cells, given as axial coordinates. Cells off the board
+    /// are ignored.
+    pub fn update(&mut self, ctx: &WgpuContext, live: impl Iterator<Item = [i32; 2]>) {
+        let size = self.size;
+        let width = 2 * size + 1;
+
+        self.bitmap.fill(0);
+        for [q, r] in live {
+            if q.abs().max(r.abs()).max((q + r).abs()) > size {
+                continue;
+            }
+            let index = ((r + size) * width + q + size) as usize;
+            self.bitmap[index / 32] |= 1 << (index % 32);
+        }
+
+        self.bitmaps.write(ctx, self.front, &self.bitmap);
+    }
+
+    /// Reads the front bitmap back from the GPU, blocking until it arrives.
+    /// Returns the live cells as axial coordinates, or `None` if the GPU
+    /// couldn't hand them over.
+    pub fn read(&self, ctx: &WgpuContext) -> Option<Vec<[i32; 2]>> {
+        let bytes = (self.bitmap.len() * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        let staging = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Board Readback Buffer"),
+            size: bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        self.bitmaps.copy_to(&mut encoder, self.front, &staging);
+        ctx.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        ctx.map_read(&slice)?;
+
+        let size = self.size;
+        let width = 2 * size + 1;
+        let data = slice.get_mapped_range();
+        let words: &[u32] = bytemuck::cast_slice(&data);
+
+        let mut live = vec![];
+        for (i, word) in words.iter().enumerate() {
+            for bit in 0..32 {
+                if word >> bit & 1 == 1 {
+                    let index = (i * 32 + bit) as i32;
+                    live.push([index % width - size, index / width - size]);
+                }
+            }
+        }
+        Some(live)
+    }
+
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+    pub(super) fn words(&self) -> usize {
+        self.bitmap.len()
+    }
+    /// The bitmaps, if they're storage buffers `GpuLife` can step.
+    pub(super) fn bitmap_buffers(&self) -> Option<&[wgpu::Buffer; 2]> {
+        match &self.bitmaps {
+            Bitmaps::Buffers(buffers) => Some(buffers),
+            Bitmaps::Textures(_) => None,
+        }
+    }
+    /// Index of the bitmap buffer that is drawn.
+    pub(super) fn front(&self) -> usize {
+        self.front
+    }
+    pub(super) fn swap(&mut self) {
+        self.front = 1 - self.front;
+    }
+
+    /// Draws the live cells in world space, then restores the default pipeline.
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(1, &self.bind_groups[self.front], &[]);
+        render_pass.set_vertex_buffer(0, self.hexagon.vb.slice(..));
+        render_pass.set_index_buffer(self.hexagon.ib.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_vertex_buffer(1, self.cell_buffer.slice(..));
+        render_pass.draw_indexed(0..self.hexagon.indices, 0, 0..self.num_cells);
+
+        gfx.bind_default(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The bitmap paths the adapter supports: storage buffers only with
+    /// compute shaders, textures everywhere.
+    fn paths(gfx: &Graphics) -> Vec<bool> {
+        [true, false]
+            .into_iter()
+            .filter(|compute| !compute || gfx.compute())
+            .collect()
+    }
+
+    fn sorted(mut cells: Vec<[i32; 2]>) -> Vec<[i32; 2]> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software EGL, see test_graphics"]
+    fn reads_back_what_was_uploaded() {
+        let mut gfx = test_graphics(1);
+        for compute in paths(&gfx) {
+            gfx.compute = compute;
+            let ctx = gfx.context();
+            let mut board = Board::new(&gfx, 9, [1.0; 3], [0.5; 3]);
+            assert_eq!(board.bitmap_buffers().is_some(), compute);
+
+            let cells = vec![[-9, 0], [0, 0], [3, -5], [0, 9], [9, -9]];
+            board.update(ctx, cells.iter().copied().chain([[9, 9]]));
+            let read = |board: &Board| sorted(board.read(ctx).expect("board reads back"));
+            assert_eq!(read(&board), sorted(cells.clone()));
+
+            board.advance(ctx, [[1, 1]].into_iter());
+            assert_eq!(read(&board), vec![[1, 1]]);
+            board.swap();
+            assert_eq!(read(&board), sorted(cells));
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software EGL, see test_graphics"]
+    fn draws_the_same_from_buffers_and_textures() {
+        let mut gfx = test_graphics(1);
+        gfx.set_clear_color([0.0; 3]);
+        gfx.update(&Camera::new([0.0, 0.0], 0.15));
+
+        let mut frames = vec![];
+        for compute in paths(&gfx) {
+            gfx.compute = compute;
+            let mut board = Board::new(&gfx, 4, [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+            board.update(gfx.context(), [[0, 0], [2, -1]].into_iter());
+
+            let mut frame = gfx.start_frame().expect("offscreen frames never fail");
+            let mut render_pass = frame.begin_pass(&gfx);
+            board.draw(&gfx, &mut render_pass);
+            drop(render_pass);
+            gfx.end_frame(frame);
+            frames.push(gfx.read_pixels().expect("offscreen frames can be read"));
+        }
+
+        let red = frames[0]
+            .chunks(4)
+            .filter(|pixel| pixel[0] == 255 && pixel[2] == 0)
+            .count();
+        assert!(red > 0, "no live cells drawn");
+        assert!(frames.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+}
@@ -0,0 +1,119 @@
+use super::CameraTransform;
+
+const MIN_ZOOM: f32 = 0.005;
+const MAX_ZOOM: f32 = 0.5;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    pub offset: [f32; 2],
+    pub zoom: f32,
+}
+impl Camera {
+    pub const fn new(offset: [f32; 2], zoom: f32) -> Self {
+        Self { offset, zoom }
+    }
+
+    fn scale(&self, size: [u32; 2]) -> [f32; 2] {
+        [self.zoom, self.zoom * size[0] as f32 / size[1] as f32]
+    }
+    pub(super) fn transform(&self, size: [u32; 2]) -> CameraTransform {
+        CameraTransform::new(self.scale(size), self.offset)
+    }
+
+    /// How many window pixels one world unit covers.
+    pub fn pixels_per_unit(&self, size: [u32; 2]) -> f32 {
+        self.zoom * size[0] as f32 / 2.0
+    }
+
+    /// Converts a position in window pixels into world space.
+    pub fn screen_to_world(&self, pos: [f32; 2], size: [u32; 2]) -> [f32; 2] {
+        let scale = self.scale(size);
+        let clip = [
+            pos[0] / size[0] as f32 * 2.0 - 1.0,
+            1.0 - pos[1] / size[1] as f32 * 2.0,
+        ];
+
+        [
+            clip[0] / scale[0] + self.offset[0],
+            clip[1] / scale[1] + self.offset[1],
+        ]
+    }
+
+    /// Moves the view by `delta` window pixels.
+    pub fn pan(&mut self, delta: [f32; 2], size: [u32; 2]) {
+        let scale = self.scale(size);
+        self.offset[0] -= delta[0] / size[0] as f32 * 2.0 / scale[0];
+        self.offset[1] += delta[1] / size[1] as f32 * 2.0 / scale[1];
+    }
+
+    /// Zooms by `factor` keeping the world point under `focus` in place.
+    pub fn zoom_at(&mut self, factor: f32, focus: [f32; 2], size: [u32; 2]) {
+        let before = self.screen_to_world(focus, size);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = self.screen_to_world(focus, size);
+
+        self.offset[0] += before[0] - after[0];
+        self.offset[1] += before[1] - after[1];
+    }
+}
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new([0.0, 0.0], 0.05)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: [u32; 2] = [800, 600];
+
+    fn assert_near(a: [f32; 2], b: [f32; 2]) {
+        assert!(
+            (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4,
+            "{:?} isn't {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn maps_the_window_onto_the_view() {
+        let camera = Camera::new([3.0, -2.0], 0.1);
+        assert_near(camera.screen_to_world([400.0, 300.0], SIZE), [3.0, -2.0]);
+        // The window's width spans 2 / zoom units, with y going up.
+        assert_near(camera.screen_to_world([800.0, 0.0], SIZE), [13.0, 5.5]);
+        assert_eq!(camera.pixels_per_unit(SIZE), 40.0);
+    }
+
+    #[test]
+    fn zooming_keeps_the_focus_in_place() {
+        let mut camera = Camera::new([3.0, -2.0], 0.1);
+        for (factor, focus) in [
+            (1.5, [120.0, 40.0]),
+            (0.3, [700.0, 550.0]),
+            (2.0, [0.0, 0.0]),
+        ] {
+            let before = camera.screen_to_world(focus, SIZE);
+            camera.zoom_at(factor, focus, SIZE);
+            assert_near(camera.screen_to_world(focus, SIZE), before);
+        }
+    }
+
+    #[test]
+    fn zoom_stays_in_bounds() {
+        let mut camera = Camera::default();
+        camera.zoom_at(1e6, [10.0, 10.0], SIZE);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        camera.zoom_at(1e-6, [10.0, 10.0], SIZE);
+        assert_eq!(camera.zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn panning_follows_the_finger() {
+        let mut camera = Camera::new([0.0, 0.0], 0.1);
+        let grabbed = camera.screen_to_world([200.0, 200.0], SIZE);
+        camera.pan([50.0, -30.0], SIZE);
+        assert_near(camera.screen_to_world([250.0, 170.0], SIZE), grabbed);
+    }
+}
@@ -0,0 +1,89 @@
+/// Smallest buffer allocated, in elements.
+const MIN_CAPACITY: usize = 64;
+
+/// Element count and capacity of a GPU buffer that is rewritten in place and
+/// only reallocated, doubling, when it runs out of room.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BufferCapacity {
+    len: usize,
+    capacity: usize,
+}
+impl BufferCapacity {
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            capacity: Self::grown(len),
+        }
+    }
+
+    /// Sets the element count. Returns the capacity to reallocate the buffer
+    /// with if it no longer fits.
+    pub fn resize(&mut self, len: usize) -> Option<usize> {
+        self.len = len;
+        if len <= self.capacity {
+            return None;
+        }
+        self.capacity = Self::grown(len);
+        Some(self.capacity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn grown(len: usize) -> usize {
+        len.next_power_of_two().max(MIN_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_at_the_minimum() {
+        let mut capacity = BufferCapacity::new(0);
+        assert_eq!(capacity.capacity(), MIN_CAPACITY);
+        assert_eq!(capacity.len(), 0);
+
+        // The first instances fit in the minimum buffer.
+        assert_eq!(capacity.resize(1), None);
+        assert_eq!(capacity.resize(MIN_CAPACITY), None);
+        assert_eq!(capacity.capacity(), MIN_CAPACITY);
+    }
+
+    #[test]
+    fn grows_to_powers_of_two() {
+        let mut capacity = BufferCapacity::new(0);
+        assert_eq!(capacity.resize(MIN_CAPACITY + 1), Some(2 * MIN_CAPACITY));
+        assert_eq!(capacity.resize(1000), Some(1024));
+        assert_eq!(capacity.resize(1024), None);
+        assert_eq!(capacity.resize(1025), Some(2048));
+
+        assert_eq!(BufferCapacity::new(300).capacity(), 512);
+        assert_eq!(BufferCapacity::new(3).capacity(), MIN_CAPACITY);
+    }
+
+    #[test]
+    fn never_shrinks() {
+        let mut capacity = BufferCapacity::new(4000);
+        assert_eq!(capacity.capacity(), 4096);
+        assert_eq!(capacity.resize(10), None);
+        assert_eq!(capacity.resize(0), None);
+        assert_eq!(capacity.capacity(), 4096);
+        assert_eq!(capacity.resize(4096), None);
+    }
+
+    #[test]
+    fn tracks_len() {
+        let mut capacity = BufferCapacity::new(5);
+        assert_eq!(capacity.len(), 5);
+        capacity.resize(200);
+        assert_eq!(capacity.len(), 200);
+        capacity.resize(7);
+        assert_eq!(capacity.len(), 7);
+    }
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+/// Area of the screen the graph covers, in clip space.
+const GRAPH_POS: [f32; 2] = [-0.95, -0.95];
+const GRAPH_SIZE: [f32; 2] = [0.9, 0.3];
+const LINE_WIDTH: f32 = 0.02;
+const BACKGROUND: [f32; 3] = [0.05, 0.05, 0.05];
+
+pub struct GraphSeries {
+    /// Samples in `0.0..=1.0`, oldest first.
+    pub values: Vec<f32>,
+    pub color: [f32; 3],
+}
+
+/// One plotted series. Its strip is rewritten in place as samples come in.
+struct Line {
+    mesh: InstancedMesh,
+    /// Vertices the strip's buffers have room for, and how many are used.
+    vertices: BufferCapacity,
+}
+impl Line {
+    fn new(ctx: &WgpuContext) -> Self {
+        let vertices = BufferCapacity::new(0);
+        Self {
+            mesh: InstancedMesh::new(strip_mesh(ctx, vertices.capacity()), ctx, &[]),
+            vertices,
+        }
+    }
+
+    fn update(&mut self, ctx: &WgpuContext, strip: &MeshBuilder, instance: Instance) {
+        if let Some(capacity) = self.vertices.resize(strip.vertices.len()) {
+            self.mesh.mesh = strip_mesh(ctx, capacity);
+        }
+        ctx.queue
+            .write_buffer(&self.mesh.mesh.vb, 0, bytemuck::cast_slice(&strip.vertices));
+        self.mesh.mesh.indices = strip.vertices.len() as u32;
+        self.mesh.update(ctx, &[instance]);
+    }
+}
+
+/// Buffers for a triangle strip of up to `capacity` vertices, drawn in order.
+fn strip_mesh(ctx: &WgpuContext, capacity: usize) -> Mesh {
+    let vb = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Graph Vertex Buffer"),
+        size: (capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let indices: Vec<u16> = (0..capacity as u16).collect();
+    let ib = ctx
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Graph Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+    Mesh::new(vb, ib, 0, wgpu::PrimitiveTopology::TriangleStrip)
+}
+
+/// A small line graph pinned to the bottom of the screen.
+pub struct Graph {
+    background: InstancedMesh,
+    lines: Vec<Line>,
+    _camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+}
+impl Graph {
+    /// Pixels the graph covers from the bottom of a window.
+    pub fn height(size: [u32; 2]) -> f32 {
+        (GRAPH_POS[1] + GRAPH_SIZE[1] + 1.0) / 2.0 * size[1] as f32
+    }
+
+    pub fn new(gfx: &Graphics) -> Self {
+        let (_camera_buffer, camera_bind_group) =
+            gfx.create_camera_binding(CameraTransform::IDENTITY);
+
+        let background = InstancedMesh::new(
+            MeshBuilder::new_quad().build(gfx.context()),
+            gfx.context(),
+            &[Instance::new(GRAPH_POS, GRAPH_SIZE, BACKGROUND)],
+        );
+
+        Self {
+            background,
+            lines: vec![],
+            _camera_buffer,
+            camera_bind_group,
+        }
+    }
+
+    /// Replaces the plotted lines. Every series spans the full width of the graph.
+    pub fn update(&mut self, ctx: &WgpuContext, series: &[GraphSeries]) {
+        self.lines.truncate(series.len());
+        while self.lines.len() < series.len() {
+            self.lines.push(Line::new(ctx));
+        }
+
+        for (line, series) in self.lines.iter_mut().zip(series) {
+            if series.values.len() < 2 {
+                line.vertices.resize(0);
+                continue;
+            }
+
+            let last = (series.values.len() - 1) as f32;
+            let points: Vec<[f32; 2]> = series
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| [i as f32 / last, value.clamp(0.0, 1.0)])
+                .collect();
+
+            // Keep the line inside the background at the extremes.
+            let margin = LINE_WIDTH / GRAPH_SIZE[1];
+            let scale = [GRAPH_SIZE[0], GRAPH_SIZE[1] * (1.0 - margin)];
+            let offset = [GRAPH_POS[0], GRAPH_POS[1] + GRAPH_SIZE[1] * margin / 2.0];
+
+            line.update(
+                ctx,
+                &MeshBuilder::new_polyline(&points, margin),
+                Instance::new(offset, scale, series.color),
+            );
+        }
+    }
+
+    /// Draws over whatever is already in the pass. This rebinds the camera, so
+    /// world-space meshes must be drawn first.
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        self.background.draw(gfx, render_pass);
+        for line in &self.lines {
+            if line.vertices.len() > 0 {
+                line.mesh.draw(gfx, render_pass);
+            }
+        }
+    }
+}
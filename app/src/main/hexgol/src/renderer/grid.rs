@@ -0,0 +1,72 @@
+use super::*;
+use crate::game::{border_edges, HexFract, HexInt};
+
+/// Hexagons smaller than this many pixels across their radius get the coarse grid.
+const MIN_CELL_PIXELS: f32 = 6.0;
+/// Cells between the lines of the coarse grid.
+const COARSE_STEP: i32 = 5;
+
+/// Outlines of every cell of a hexagonal board, and its border. When zoomed
+/// out the outlines give way to lines through every `COARSE_STEP`th row along
+/// each axis.
+pub struct Grid {
+    cells: InstancedMesh,
+    coarse: InstancedMesh,
+    border: InstancedMesh,
+}
+impl Grid {
+    pub fn new(gfx: &Graphics, size: i32, color: [f32; 3], border_color: [f32; 3]) -> Self {
+        let ctx = gfx.context();
+        let center = |q: i32, r: i32| HexFract::from(HexInt::new(q, r)).transform(1.0);
+
+        let mut cells = vec![];
+        for q in -size..=size {
+            for r in (-size).max(-q - size)..=size.min(-q + size) {
+                cells.push(Instance::new(center(q, r), [1.0, 1.0], color));
+            }
+        }
+        let border = border_edges(size);
+
+        // Lines of constant q, r and s through cell centres.
+        let mut coarse = vec![];
+        for i in (-size..=size).filter(|i| i % COARSE_STEP == 0) {
+            let first = (-size).max(-i - size);
+            let last = size.min(-i + size);
+            coarse.push([center(i, first), center(i, last)]);
+            coarse.push([center(first, i), center(last, i)]);
+            coarse.push([center(first, -i - first), center(last, -i - last)]);
+        }
+
+        let outline = MeshBuilder::new_hexagon_outline([0.0, 0.0], 1.0).build(ctx);
+        let single = [Instance::new([0.0, 0.0], [1.0, 1.0], color)];
+        Self {
+            cells: InstancedMesh::new(outline, ctx, &cells),
+            coarse: InstancedMesh::new(MeshBuilder::new_lines(&coarse).build(ctx), ctx, &single),
+            border: InstancedMesh::new(
+                MeshBuilder::new_lines(&border).build(ctx),
+                ctx,
+                &[Instance::new([0.0, 0.0], [1.0, 1.0], border_color)],
+            ),
+        }
+    }
+
+    /// Draws the cell outlines, or the coarse grid if cells are too small to
+    /// tell apart. Goes under the live cells.
+    pub fn draw_lines<'a>(
+        &'a self,
+        gfx: &'a Graphics,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pixels_per_cell: f32,
+    ) {
+        if pixels_per_cell >= MIN_CELL_PIXELS {
+            self.cells.draw(gfx, render_pass);
+        } else {
+            self.coarse.draw(gfx, render_pass);
+        }
+    }
+
+    /// Draws the outline of the board. Goes over the live cells.
+    pub fn draw_border<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.border.draw(gfx, render_pass);
+    }
+}
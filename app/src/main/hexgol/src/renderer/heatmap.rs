@@ -0,0 +1,70 @@
+use super::*;
+use crate::game::{HexFract, HexInt};
+
+/// Colours of the heat ramp, from cold to hot.
+const RAMP: [[f32; 3]; 4] = [
+    [0.05, 0.0, 0.2],
+    [0.8, 0.1, 0.1],
+    [1.0, 0.6, 0.0],
+    [1.0, 1.0, 0.8],
+];
+/// Opacity of a trail right behind a cell.
+const TRAIL_ALPHA: f32 = 0.6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HeatStyle {
+    /// Values run along the heat ramp.
+    Heat,
+    /// Values fade the given colour out.
+    Trail([f32; 3]),
+}
+
+/// Cells shaded by a value in `0.0..=1.0`, drawn in world space under the board.
+pub struct Heatmap {
+    cells: InstancedMesh,
+}
+impl Heatmap {
+    pub fn new(gfx: &Graphics) -> Self {
+        let hex = MeshBuilder::new_hexagon([0.0, 0.0], 1.0).build(gfx.context());
+        Self {
+            cells: InstancedMesh::new(hex, gfx.context(), &[]),
+        }
+    }
+
+    /// Takes cells as axial coordinates with their values.
+    pub fn update(
+        &mut self,
+        ctx: &WgpuContext,
+        cells: impl Iterator<Item = ([i32; 2], f32)>,
+        style: HeatStyle,
+    ) {
+        let instances: Vec<Instance> = cells
+            .map(|([q, r], value)| {
+                let pos = HexFract::from(HexInt::new(q, r)).transform(1.0);
+                match style {
+                    HeatStyle::Heat => Instance::new(pos, [1.0, 1.0], ramp(value)),
+                    HeatStyle::Trail(color) => {
+                        Instance::new(pos, [1.0, 1.0], color).with_alpha(value * TRAIL_ALPHA)
+                    }
+                }
+            })
+            .collect();
+        self.cells.update(ctx, &instances);
+    }
+
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.cells.draw(gfx, render_pass);
+    }
+}
+
+fn ramp(value: f32) -> [f32; 3] {
+    let x = value.clamp(0.0, 1.0) * (RAMP.len() - 1) as f32;
+    let i = (x as usize).min(RAMP.len() - 2);
+    let t = x - i as f32;
+    let (a, b) = (RAMP[i], RAMP[i + 1]);
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
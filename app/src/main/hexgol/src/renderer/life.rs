@@ -0,0 +1,218 @@
+use super::*;
+use crate::game::{Neighborhood, Rule, Topology};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LifeUniform {
+    birth: u32,
+    survive: u32,
+    size: i32,
+    width: i32,
+    /// Whether the diagonal cells count as neighbours too.
+    extended: u32,
+    /// Whether the edges wrap around.
+    wrapped: u32,
+    _padding: [u32; 2],
+}
+
+/// Steps a `Board`'s bitmap on the GPU with a compute shader, reading the
+/// front buffer and writing the back one, then flipping them. The tests check
+/// it against the CPU `HexGOL`.
+///
+/// Only devices with compute shaders and storage buffers in vertex shaders can
+/// run it; see `Graphics::compute`.
+pub struct GpuLife {
+    pipeline: wgpu::ComputePipeline,
+    uniform_buffer: wgpu::Buffer,
+    /// `bind_groups[i]` reads bitmap `i` and writes the other.
+    bind_groups: [wgpu::BindGroup; 2],
+    size: i32,
+    workgroups: u32,
+}
+impl GpuLife {
+    /// `None` if the board's bitmaps aren't storage buffers, because the
+    /// device can't run compute shaders.
+    pub fn new(gfx: &Graphics, board: &Board) -> Option<Self> {
+        let device = &gfx.context().device;
+        let buffers = board.bitmap_buffers()?;
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Life Uniform Buffer"),
+            size: std::mem::size_of::<LifeUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+            ],
+            label: Some("Life bind group"),
+        });
+
+        let bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffers[i].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffers[1 - i].as_entire_binding(),
+                    },
+                ],
+                label: Some("life_bind_group"),
+            })
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("../life.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Life Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Life Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        Some(Self {
+            pipeline,
+            uniform_buffer,
+            bind_groups,
+            size: board.size(),
+            workgroups: (board.words() as u32).div_ceil(WORKGROUP_SIZE),
+        })
+    }
+
+    /// Runs `generations` steps. The result is left in the board's front buffer.
+    pub fn step(
+        &self,
+        ctx: &WgpuContext,
+        board: &mut Board,
+        rule: Rule,
+        neighborhood: Neighborhood,
+        topology: Topology,
+        generations: u32,
+    ) {
+        ctx.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[LifeUniform {
+                birth: rule.birth(),
+                survive: rule.survive(),
+                size: self.size,
+                width: 2 * self.size + 1,
+                extended: (neighborhood == Neighborhood::Extended) as u32,
+                wrapped: (topology == Topology::Wrapped) as u32,
+                _padding: [0; 2],
+            }]),
+        );
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Life Encoder"),
+            });
+        for _ in 0..generations {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Life Pass"),
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &self.bind_groups[board.front()], &[]);
+            compute_pass.dispatch_workgroups(self.workgroups, 1, 1);
+            drop(compute_pass);
+
+            board.swap();
+        }
+        ctx.queue.submit([encoder.finish()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::HexGOL;
+
+    fn live(game: &HexGOL) -> Vec<[i32; 2]> {
+        let mut cells: Vec<[i32; 2]> = game
+            .iter()
+            .filter(|(_hex, cell)| **cell)
+            .map(|(hex, _cell)| [hex.q(), hex.r()])
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software EGL, see test_graphics"]
+    fn matches_the_cpu() {
+        let gfx = test_graphics(1);
+        let ctx = gfx.context();
+
+        for neighborhood in Neighborhood::ALL {
+            for topology in Topology::ALL {
+                for rule in ["B2/S2", "B2/S34", "B24/S35"] {
+                    let rule: Rule = rule.parse().unwrap();
+                    let mut game = HexGOL::new(9);
+                    game.set_rule(rule);
+                    game.set_neighborhood(neighborhood);
+                    game.set_topology(topology);
+                    game.randomize();
+
+                    let mut board = Board::new(&gfx, game.size(), [1.0; 3], [0.5; 3]);
+                    let life =
+                        GpuLife::new(&gfx, &board).expect("the adapter runs compute shaders");
+                    board.update(ctx, live(&game).into_iter());
+
+                    for generation in 1..=8 {
+                        life.step(ctx, &mut board, rule, neighborhood, topology, 1);
+                        game.update();
+                        let mut gpu = board.read(ctx).expect("board reads back");
+                        gpu.sort();
+                        assert_eq!(
+                            gpu,
+                            live(&game),
+                            "{} {:?} {:?} at generation {}",
+                            rule,
+                            neighborhood,
+                            topology,
+                            generation
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,28 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use wgpu::util::DeviceExt;
 
+mod camera;
+pub use camera::*;
+mod capacity;
+use capacity::*;
+mod graph;
+pub use graph::*;
+mod board;
+pub use board::*;
+mod grid;
+pub use grid::*;
+mod life;
+pub use life::*;
+mod overlay;
+pub use overlay::*;
+mod screenshot;
+pub use screenshot::*;
+mod heatmap;
+pub use heatmap::*;
+mod text;
+pub use text::*;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
@@ -29,14 +52,29 @@ pub struct CameraTransform {
     offset: [f32; 2],
 }
 impl CameraTransform {
+    /// Maps world space straight onto clip space.
+    const IDENTITY: Self = Self {
+        scale: [1.0, 1.0],
+        offset: [0.0, 0.0],
+    };
+
     fn new(scale: [f32; 2], offset: [f32; 2]) -> Self {
         Self { scale, offset }
     }
+    /// Maps window pixels, y down, onto clip space.
+    fn pixels(size: [u32; 2]) -> Self {
+        let size = [size[0] as f32, size[1] as f32];
+        Self::new(
+            [2.0 / size[0], -2.0 / size[1]],
+            [size[0] / 2.0, size[1] / 2.0],
+        )
+    }
 }
 
 pub struct MeshBuilder {
     vertices: Vec<Vertex>,
     indices: Vec<u16>,
+    topology: wgpu::PrimitiveTopology,
 }
 impl MeshBuilder {
     pub fn new_hexagon(pos: [f32; 2], size: f32) -> Self {
@@ -54,7 +92,63 @@ impl MeshBuilder {
 
         let indices = vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 5];
 
-        Self { vertices, indices }
+        Self {
+            vertices,
+            indices,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+        }
+    }
+    /// The border of a hexagon, as a closed line.
+    pub fn new_hexagon_outline(pos: [f32; 2], size: f32) -> Self {
+        let mut hexagon = Self::new_hexagon(pos, size);
+        hexagon.indices = vec![0, 1, 2, 3, 4, 5, 0];
+        hexagon.topology = wgpu::PrimitiveTopology::LineStrip;
+        hexagon
+    }
+    /// Separate line segments, one per pair of points.
+    pub fn new_lines(segments: &[[[f32; 2]; 2]]) -> Self {
+        let vertices: Vec<Vertex> = segments
+            .iter()
+            .flat_map(|segment| segment.iter().map(|point| Vertex::new(*point)))
+            .collect();
+        let indices = (0..vertices.len() as u16).collect();
+
+        Self {
+            vertices,
+            indices,
+            topology: wgpu::PrimitiveTopology::LineList,
+        }
+    }
+    /// A unit quad from (0, 0) to (1, 1).
+    pub fn new_quad() -> Self {
+        let vertices = vec![
+            Vertex::new([0.0, 0.0]),
+            Vertex::new([1.0, 0.0]),
+            Vertex::new([0.0, 1.0]),
+            Vertex::new([1.0, 1.0]),
+        ];
+        let indices = vec![0, 1, 2, 3];
+
+        Self {
+            vertices,
+            indices,
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+        }
+    }
+    /// A strip of the given vertical thickness following `points`.
+    pub fn new_polyline(points: &[[f32; 2]], width: f32) -> Self {
+        let mut vertices = Vec::with_capacity(points.len() * 2);
+        for point in points {
+            vertices.push(Vertex::new([point[0], point[1] - width / 2.0]));
+            vertices.push(Vertex::new([point[0], point[1] + width / 2.0]));
+        }
+        let indices = (0..vertices.len() as u16).collect();
+
+        Self {
+            vertices,
+            indices,
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+        }
     }
     pub fn build(&self, ctx: &WgpuContext) -> Mesh {
         let vb = ctx
@@ -72,7 +166,7 @@ impl MeshBuilder {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        Mesh::new(vb, ib, self.indices.len() as u32)
+        Mesh::new(vb, ib, self.indices.len() as u32, self.topology)
     }
 }
 
@@ -80,10 +174,21 @@ pub struct Mesh {
     vb: wgpu::Buffer,
     ib: wgpu::Buffer,
     indices: u32,
+    topology: wgpu::PrimitiveTopology,
 }
 impl Mesh {
-    pub fn new(vb: wgpu::Buffer, ib: wgpu::Buffer, indices: u32) -> Self {
-        Self { vb, ib, indices }
+    pub fn new(
+        vb: wgpu::Buffer,
+        ib: wgpu::Buffer,
+        indices: u32,
+        topology: wgpu::PrimitiveTopology,
+    ) -> Self {
+        Self {
+            vb,
+            ib,
+            indices,
+            topology,
+        }
     }
 }
 
@@ -92,19 +197,23 @@ impl Mesh {
 pub struct Instance {
     scale: [f32; 2],
     offset: [f32; 2],
-    color: [f32; 3],
+    color: [f32; 4],
 }
 impl Instance {
     pub fn new(offset: [f32; 2], scale: [f32; 2], color: [f32; 3]) -> Self {
         Self {
             scale,
             offset,
-            color,
+            color: [color[0], color[1], color[2], 1.0],
         }
     }
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.color[3] = alpha;
+        self
+    }
 
     const ATTRIBS: [wgpu::VertexAttribute; 3] =
-        wgpu::vertex_attr_array![1 => Float32x2, 2 => Float32x2, 3 => Float32x3];
+        wgpu::vertex_attr_array![1 => Float32x2, 2 => Float32x2, 3 => Float32x4];
 
     fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         use std::mem;
@@ -116,130 +225,462 @@ impl Instance {
         }
     }
 }
+/// A mesh drawn once per instance. The instance buffer is kept between
+/// updates and only reallocated when it has to grow.
 pub struct InstancedMesh {
     mesh: Mesh,
     instance_buffer: wgpu::Buffer,
-    num_instances: u32,
+    capacity: BufferCapacity,
 }
 impl InstancedMesh {
     pub fn new(mesh: Mesh, ctx: &WgpuContext, instances: &[Instance]) -> Self {
-        let instance_buffer = ctx
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Instance Buffer"),
-                contents: bytemuck::cast_slice(instances),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-
-        let num_instances = instances.len() as u32;
+        let capacity = BufferCapacity::new(instances.len());
+        let instance_buffer = create_instance_buffer(ctx, capacity.capacity());
+        ctx.queue
+            .write_buffer(&instance_buffer, 0, bytemuck::cast_slice(instances));
 
         Self {
             mesh,
             instance_buffer,
-            num_instances,
+            capacity,
         }
     }
     pub fn update(&mut self, ctx: &WgpuContext, instances: &[Instance]) {
-        self.instance_buffer = ctx
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Instance Buffer"),
-                contents: bytemuck::cast_slice(instances),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-        self.num_instances = instances.len() as u32;
+        if let Some(capacity) = self.capacity.resize(instances.len()) {
+            self.instance_buffer = create_instance_buffer(ctx, capacity);
+        }
+        ctx.queue
+            .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(instances));
     }
-    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        // Nothing to draw, skip the bindings too.
+        if self.capacity.len() == 0 {
+            return;
+        }
+        render_pass.set_pipeline(gfx.pipeline(self.mesh.topology));
         render_pass.set_vertex_buffer(0, self.mesh.vb.slice(..));
         render_pass.set_index_buffer(self.mesh.ib.slice(..), wgpu::IndexFormat::Uint16);
         render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-        render_pass.draw_indexed(0..self.mesh.indices, 0, 0..self.num_instances);
+        render_pass.draw_indexed(0..self.mesh.indices, 0, 0..self.capacity.len() as u32);
+    }
+}
+
+/// The requested sample count if the adapter can render and resolve it, or the
+/// closest it can. wgpu only says whether a format multisamples at all, and
+/// only 4 samples are guaranteed then.
+fn supported_sample_count(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    requested: u32,
+) -> u32 {
+    use wgpu::TextureFormatFeatureFlags as Flags;
+
+    if requested <= 1 {
+        return 1;
+    }
+    let flags = adapter.get_texture_format_features(format).flags;
+    let count = if flags.contains(Flags::MULTISAMPLE | Flags::MULTISAMPLE_RESOLVE) {
+        4
+    } else {
+        1
+    };
+    if count != requested {
+        log::warn!(
+            "{}x multisampling isn't supported, using {}x",
+            requested,
+            count
+        );
+    }
+    count
+}
+
+fn create_msaa_view(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count == 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Multisampled Frame"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+fn create_instance_buffer(ctx: &WgpuContext, capacity: usize) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: (capacity * std::mem::size_of::<Instance>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Graphics APIs the renderer may use.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GraphicsBackend {
+    /// Vulkan, falling back to OpenGL ES.
+    #[default]
+    Auto,
+    Vulkan,
+    Gles,
+}
+impl GraphicsBackend {
+    pub const ALL: [Self; 3] = [Self::Auto, Self::Vulkan, Self::Gles];
+
+    /// Backends to try, in order.
+    fn order(&self) -> &'static [wgpu::Backends] {
+        match self {
+            Self::Auto => &[wgpu::Backends::VULKAN, wgpu::Backends::GL],
+            Self::Vulkan => &[wgpu::Backends::VULKAN],
+            Self::Gles => &[wgpu::Backends::GL],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Vulkan => "vulkan",
+            Self::Gles => "gles",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|b| b.name() == name)
+    }
+}
+
+/// What simulating on the GPU needs beyond WebGL2's baseline: `GpuLife` is a
+/// compute shader, and the board's vertex shader reads the storage buffers it
+/// writes.
+const COMPUTE_DOWNLEVEL: wgpu::DownlevelFlags =
+    wgpu::DownlevelFlags::COMPUTE_SHADERS.union(wgpu::DownlevelFlags::VERTEX_STORAGE);
+
+/// Why rendering couldn't be set up.
+#[derive(Debug)]
+pub enum GraphicsError {
+    NoAdapter,
+    NoDevice(wgpu::RequestDeviceError),
+    /// The adapter can't present to the window.
+    UnsupportedSurface,
+}
+impl std::fmt::Display for GraphicsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no graphics adapter supports this device"),
+            Self::NoDevice(err) => write!(f, "couldn't open the graphics device: {}", err),
+            Self::UnsupportedSurface => write!(f, "the graphics adapter can't draw to the window"),
+        }
     }
 }
 
 pub struct WgpuContext {
     pub instance: wgpu::Instance,
-    pub surface: wgpu::Surface,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
 }
+impl WgpuContext {
+    /// Maps a readback buffer, blocking until it's done. `None` if mapping
+    /// failed, in which case the buffer can't be read.
+    fn map_read(&self, slice: &wgpu::BufferSlice) -> Option<()> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            // Nobody is waiting any more if the receiver is gone.
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        match receiver.recv() {
+            Ok(Ok(())) => Some(()),
+            Ok(Err(err)) => {
+                log::error!("Couldn't read back from the GPU: {}", err);
+                None
+            }
+            Err(_) => {
+                log::error!("The GPU never finished reading back");
+                None
+            }
+        }
+    }
+}
+
+/// Where frames end up.
+enum Target {
+    Window(wgpu::Surface),
+    /// A texture frames can be read back from.
+    Texture(wgpu::Texture),
+}
 
-struct Frame {
-    output: wgpu::SurfaceTexture,
+/// Format of offscreen frames, as bytes in the order `read_pixels` returns.
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+fn create_offscreen_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Frame"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    })
+}
+
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+    force_fallback_adapter: bool,
+) -> Result<wgpu::Adapter, GraphicsError> {
+    instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: surface,
+            force_fallback_adapter,
+        })
+        .await
+        .ok_or(GraphicsError::NoAdapter)
+}
+
+/// A frame being recorded. Passes borrow the frame rather than `Graphics`, so
+/// pipelines can still be switched while drawing.
+pub struct Frame {
+    /// `None` offscreen, where there's nothing to present.
+    output: Option<wgpu::SurfaceTexture>,
     view: wgpu::TextureView,
     encoder: wgpu::CommandEncoder,
 }
+impl Frame {
+    /// Clears the frame and starts drawing with the default pipeline and the
+    /// world camera.
+    pub fn begin_pass<'a>(&'a mut self, gfx: &'a Graphics) -> wgpu::RenderPass<'a> {
+        // With multisampling, draw into the multisampled texture and resolve
+        // into the frame.
+        let (view, resolve_target) = match &gfx.msaa_view {
+            Some(msaa_view) => (msaa_view, Some(&self.view)),
+            None => (&self.view, None),
+        };
+        let mut render_pass = self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(gfx.clear_color),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        gfx.bind_default(&mut render_pass);
+        render_pass
+    }
+}
+
+/// Topologies meshes can be drawn with.
+const TOPOLOGIES: [wgpu::PrimitiveTopology; 4] = [
+    wgpu::PrimitiveTopology::TriangleList,
+    wgpu::PrimitiveTopology::TriangleStrip,
+    wgpu::PrimitiveTopology::LineList,
+    wgpu::PrimitiveTopology::LineStrip,
+];
 
 pub struct Graphics {
     ctx: WgpuContext,
+    target: Target,
     config: wgpu::SurfaceConfiguration,
-    render_pipeline: wgpu::RenderPipeline,
-
-    frame: Option<Frame>,
-
-    camera: CameraTransform,
+    /// One pipeline per entry of `TOPOLOGIES`.
+    render_pipelines: [wgpu::RenderPipeline; 4],
+    sample_count: u32,
+    /// Multisampled colour target, resized with the surface. `None` without
+    /// multisampling.
+    msaa_view: Option<wgpu::TextureView>,
+
+    camera_bind_group_layout: wgpu::BindGroupLayout,
     camera_bind_group: wgpu::BindGroup,
     camera_buffer: wgpu::Buffer,
+
+    clear_color: wgpu::Color,
+    /// Whether `GpuLife` can run, see `COMPUTE_DOWNLEVEL`.
+    compute: bool,
+    /// Set once the device reports running out of memory, after which it
+    /// can't be trusted.
+    lost: Arc<AtomicBool>,
 }
 impl Graphics {
-    pub async fn new<W>(size: [u32; 2], window: &W) -> Self
+    /// `sample_count` is the requested multisampling, which falls back to what
+    /// the adapter supports. The backends `backend` allows are tried in turn.
+    pub async fn new<W>(
+        size: [u32; 2],
+        window: &W,
+        sample_count: u32,
+        backend: GraphicsBackend,
+    ) -> Result<Self, GraphicsError>
+    where
+        W: raw_window_handle::HasRawWindowHandle + raw_window_handle::HasRawDisplayHandle,
+    {
+        let mut failure = GraphicsError::NoAdapter;
+        for backends in backend.order() {
+            match Self::with_backends(*backends, size, window, sample_count).await {
+                Ok(gfx) => return Ok(gfx),
+                Err(err) => {
+                    log::warn!("Can't render with {:?}: {}", backends, err);
+                    failure = err;
+                }
+            }
+        }
+        Err(failure)
+    }
+
+    async fn with_backends<W>(
+        backends: wgpu::Backends,
+        size: [u32; 2],
+        window: &W,
+        sample_count: u32,
+    ) -> Result<Self, GraphicsError>
     where
         W: raw_window_handle::HasRawWindowHandle + raw_window_handle::HasRawDisplayHandle,
     {
         // The instance is a handle to our GPU
-        let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+        let instance = wgpu::Instance::new(backends);
 
         let surface = unsafe { instance.create_surface(window) };
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
+        let adapter = request_adapter(&instance, Some(&surface), false).await?;
+        let format = *surface
+            .get_supported_formats(&adapter)
+            .first()
+            .ok_or(GraphicsError::UnsupportedSurface)?;
+
+        Self::with_adapter(instance, adapter, Some(surface), format, size, sample_count).await
+    }
+
+    /// Draws into a texture instead of a window, to be read back with
+    /// `read_pixels`. Any adapter will do, down to a software one, so this
+    /// works on machines without a GPU.
+    pub async fn headless(size: [u32; 2], sample_count: u32) -> Result<Self, GraphicsError> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        // Software adapters are only offered as fallbacks.
+        let adapter = match request_adapter(&instance, None, false).await {
+            Ok(adapter) => adapter,
+            Err(_) => request_adapter(&instance, None, true).await?,
+        };
+        Self::with_adapter(
+            instance,
+            adapter,
+            None,
+            OFFSCREEN_FORMAT,
+            size,
+            sample_count,
+        )
+        .await
+    }
+
+    async fn with_adapter(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        surface: Option<wgpu::Surface>,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+        sample_count: u32,
+    ) -> Result<Self, GraphicsError> {
+        // Logged so bug reports say what the app actually ran on.
+        let info = adapter.get_info();
+        log::info!(
+            "Adapter: {} ({:?}, {:?}), driver {} {}",
+            info.name,
+            info.backend,
+            info.device_type,
+            info.driver,
+            info.driver_info
+        );
+
+        // GLES devices rarely reach the default limits, and nothing here needs
+        // more than the downlevel ones. GLES 3.0 has no compute shaders or
+        // storage buffers at all, so there the board is drawn from textures
+        // and only the CPU simulates.
+        let compute_limits = wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits());
+        let compute = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(COMPUTE_DOWNLEVEL)
+            && compute_limits.check_limits(&adapter.limits());
+        let limits = if compute {
+            compute_limits
+        } else {
+            log::info!("No compute shaders, simulating on the CPU only");
+            wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+        };
 
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
+                    limits,
                     label: None,
                 },
                 None, // Trace path
             )
             .await
-            .unwrap();
+            .map_err(GraphicsError::NoDevice)?;
+
+        // wgpu panics on errors nobody handles. Validation errors are bugs,
+        // but the frame they spoil is better skipped than the app closed.
+        let lost = Arc::new(AtomicBool::new(false));
+        device.on_uncaptured_error({
+            let lost = lost.clone();
+            move |err| match err {
+                wgpu::Error::OutOfMemory { source } => {
+                    log::error!("GPU out of memory: {}", source);
+                    lost.store(true, Ordering::Relaxed);
+                }
+                wgpu::Error::Validation { description, .. } => {
+                    log::error!("wgpu: {}", description)
+                }
+            }
+        });
 
         let ctx = WgpuContext {
             instance,
-            surface,
             device,
             queue,
         };
 
+        // Offscreen, this only records the size and format.
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: ctx.surface.get_supported_formats(&adapter)[0],
+            format,
             width: size[0],
             height: size[1],
             present_mode: wgpu::PresentMode::Fifo,
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
         };
-        ctx.surface.configure(&ctx.device, &config);
+        let target = match surface {
+            Some(surface) => {
+                surface.configure(&ctx.device, &config);
+                Target::Window(surface)
+            }
+            None => Target::Texture(create_offscreen_texture(&ctx.device, &config)),
+        };
 
-        let scale = 0.05;
-        let camera = CameraTransform::new([1.0 * scale, 1.0 * scale], [0.0, 0.0]);
-        let mut cb = camera.clone();
-        cb.scale[1] *= size[0] as f32 / size[1] as f32;
-        let camera_buffer = ctx
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Camera Buffer"),
-                contents: bytemuck::cast_slice(&[cb]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
+        let sample_count = supported_sample_count(&adapter, config.format, sample_count);
+        let msaa_view = create_msaa_view(&ctx.device, &config, sample_count);
 
         let camera_transform_bind_group_layout =
             ctx.device
@@ -257,14 +698,11 @@ impl Graphics {
                     label: Some("Transform bind group"),
                 });
 
-        let camera_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &camera_transform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
-        });
+        let (camera_buffer, camera_bind_group) = create_camera_binding(
+            &ctx.device,
+            &camera_transform_bind_group_layout,
+            Camera::default().transform(size),
+        );
 
         let shader = ctx
             .device
@@ -276,108 +714,203 @@ impl Graphics {
                     bind_group_layouts: &[&camera_transform_bind_group_layout],
                     push_constant_ranges: &[],
                 });
-        let render_pipeline = ctx
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::desc(), Instance::desc()],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleStrip,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Cw,
-                    cull_mode: None,
-                    // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    // Requires Features::DEPTH_CLIP_CONTROL
-                    unclipped_depth: false,
-                    // Requires Features::CONSERVATIVE_RASTERIZATION
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
-
-        let frame = None;
+        let render_pipelines = TOPOLOGIES.map(|topology| {
+            ctx.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Render Pipeline"),
+                    layout: Some(&render_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[Vertex::desc(), Instance::desc()],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: config.format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Cw,
+                        cull_mode: None,
+                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        // Requires Features::DEPTH_CLIP_CONTROL
+                        unclipped_depth: false,
+                        // Requires Features::CONSERVATIVE_RASTERIZATION
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                })
+        });
 
-        Self {
+        Ok(Self {
             ctx,
+            target,
             config,
-            render_pipeline,
-            frame,
-            camera,
+            render_pipelines,
+            sample_count,
+            msaa_view,
+            camera_bind_group_layout: camera_transform_bind_group_layout,
             camera_bind_group,
             camera_buffer,
-        }
+            clear_color: wgpu::Color {
+                r: 0.01,
+                g: 0.01,
+                b: 0.01,
+                a: 1.0,
+            },
+            compute,
+            lost,
+        })
     }
 
-    pub fn start_frame<'a>(&'a mut self) -> wgpu::RenderPass<'a> {
-        let output = self.ctx.surface.get_current_texture().unwrap();
+    /// The next frame, or `None` to skip this one. A surface that went out of
+    /// date is reconfigured in time for the next.
+    pub fn start_frame(&mut self) -> Option<Frame> {
+        let surface = match &self.target {
+            Target::Window(surface) => surface,
+            Target::Texture(texture) => {
+                return Some(self.frame(
+                    None,
+                    texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                ))
+            }
+        };
+        let output = match surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                log::info!("Reconfiguring the surface");
+                surface.configure(&self.ctx.device, &self.config);
+                return None;
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                log::warn!("Timed out waiting for a frame");
+                return None;
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("Out of memory for a frame");
+                self.lost.store(true, Ordering::Relaxed);
+                return None;
+            }
+        };
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        Some(self.frame(Some(output), view))
+    }
+
+    fn frame(&self, output: Option<wgpu::SurfaceTexture>, view: wgpu::TextureView) -> Frame {
         let encoder = self
             .ctx
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
-
-        self.frame = Some(Frame {
+        Frame {
             output,
             view,
             encoder,
-        });
+        }
+    }
 
-        let f = self.frame.as_mut().unwrap();
+    pub fn end_frame(&self, frame: Frame) {
+        self.ctx.queue.submit([frame.encoder.finish()]);
+        if let (Some(output), Target::Window(surface)) = (frame.output, &self.target) {
+            let suboptimal = output.suboptimal;
+            output.present();
+            if suboptimal {
+                surface.configure(&self.ctx.device, &self.config);
+            }
+        }
+    }
 
-        let mut render_pass = f.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &f.view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.01,
-                        g: 0.01,
-                        b: 0.01,
-                        a: 1.0,
-                    }),
-                    store: true,
-                },
-            })],
-            depth_stencil_attachment: None,
+    /// The last frame drawn offscreen, as tightly packed RGBA rows from the
+    /// top, blocking until it arrives. `None` when drawing to a window.
+    pub fn read_pixels(&self) -> Option<Vec<u8>> {
+        let texture = match &self.target {
+            Target::Texture(texture) => texture,
+            Target::Window(_) => return None,
+        };
+        let [width, height] = [self.config.width, self.config.height];
+        let row = width * 4;
+        // Copies need rows padded to a fixed alignment.
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = row + (align - row % align) % align;
+
+        let staging = self.ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Readback Buffer"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
-        render_pass.set_pipeline(&self.render_pipeline);
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &staging,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.ctx.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        self.ctx.map_read(&slice)?;
+
+        let data = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((row * height) as usize);
+        for padded in data.chunks(padded_row as usize) {
+            pixels.extend_from_slice(&padded[..row as usize]);
+        }
+        Some(pixels)
+    }
+
+    /// Whether the device failed and everything on it has to be recreated.
+    /// wgpu 0.14 panics rather than report most kinds of device loss, so this
+    /// only catches running out of memory.
+    pub fn lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Restores the default pipeline and world camera after drawing with others.
+    pub fn bind_default<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.render_pipelines[0]);
         render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        render_pass
     }
 
-    pub fn end_frame(&mut self) {
-        let frame = self
-            .frame
-            .take()
-            .expect("Ended frame without starting one!");
-        self.ctx.queue.submit([frame.encoder.finish()]);
-        frame.output.present();
+    /// The pipeline for meshes with the given topology. They all share a
+    /// layout, so bound cameras survive switching between them.
+    pub fn pipeline(&self, topology: wgpu::PrimitiveTopology) -> &wgpu::RenderPipeline {
+        let index = TOPOLOGIES
+            .iter()
+            .position(|t| *t == topology)
+            .expect("No pipeline for topology");
+        &self.render_pipelines[index]
     }
 
     pub fn context(&self) -> &WgpuContext {
@@ -385,20 +918,92 @@ impl Graphics {
     }
 
     pub fn resize(&mut self, new_size: [u32; 2]) {
+        // A surface can't be configured with no area.
+        if new_size.contains(&0) {
+            return;
+        }
         self.config.width = new_size[0];
         self.config.height = new_size[1];
-        self.ctx.surface.configure(&self.ctx.device, &self.config);
+        match &mut self.target {
+            Target::Window(surface) => surface.configure(&self.ctx.device, &self.config),
+            Target::Texture(texture) => {
+                *texture = create_offscreen_texture(&self.ctx.device, &self.config)
+            }
+        }
+        self.msaa_view = create_msaa_view(&self.ctx.device, &self.config, self.sample_count);
+    }
+
+    /// A separate camera uniform for overlays that don't follow the world camera.
+    fn create_camera_binding(&self, camera: CameraTransform) -> (wgpu::Buffer, wgpu::BindGroup) {
+        create_camera_binding(&self.ctx.device, &self.camera_bind_group_layout, camera)
+    }
+
+    pub fn set_clear_color(&mut self, color: [f32; 3]) {
+        self.clear_color = wgpu::Color {
+            r: color[0] as f64,
+            g: color[1] as f64,
+            b: color[2] as f64,
+            a: 1.0,
+        };
     }
 
-    pub fn update(&mut self) {
-        let width = self.config.width as f32;
-        let height = self.config.height as f32;
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+    /// Whether the board can be simulated on the GPU with `GpuLife`.
+    pub fn compute(&self) -> bool {
+        self.compute
+    }
+    /// Samples per pixel every render pipeline has to be created with.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        [self.config.width, self.config.height]
+    }
 
-        let mut cb = self.camera.clone();
-        cb.scale[1] *= width as f32 / height as f32;
+    pub fn update(&mut self, camera: &Camera) {
+        let cb = camera.transform(self.size());
 
         self.ctx
             .queue
-            .write_buffer(&self.camera_buffer, 0, &bytemuck::cast_slice(&[cb]));
+            .write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[cb]));
     }
 }
+
+fn create_camera_binding(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    camera: CameraTransform,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Camera Buffer"),
+        contents: bytemuck::cast_slice(&[camera]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+        label: Some("camera_bind_group"),
+    });
+
+    (buffer, bind_group)
+}
+
+/// Headless graphics for the tests that draw or simulate, failing them
+/// without an adapter. Those tests are ignored by default. Without a GPU,
+/// Mesa's software renderer runs them:
+///
+/// ```sh
+/// XDG_RUNTIME_DIR=/tmp LIBGL_ALWAYS_SOFTWARE=1 EGL_PLATFORM=surfaceless \
+///     cargo test -- --include-ignored
+/// ```
+#[cfg(test)]
+pub(crate) fn test_graphics(sample_count: u32) -> Graphics {
+    pollster::block_on(Graphics::headless([64, 64], sample_count))
+        .expect("a GPU or software EGL to run on")
+}
@@ -0,0 +1,45 @@
+use super::*;
+
+/// Quads and hexagons drawn in window pixels, with y pointing down, on top of
+/// the world.
+pub struct Overlay {
+    quads: InstancedMesh,
+    hexes: InstancedMesh,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+}
+impl Overlay {
+    pub fn new(gfx: &Graphics) -> Self {
+        let (camera_buffer, camera_bind_group) =
+            gfx.create_camera_binding(CameraTransform::pixels(gfx.size()));
+
+        let quad = MeshBuilder::new_quad().build(gfx.context());
+        let hex = MeshBuilder::new_hexagon([0.0, 0.0], 1.0).build(gfx.context());
+
+        Self {
+            quads: InstancedMesh::new(quad, gfx.context(), &[]),
+            hexes: InstancedMesh::new(hex, gfx.context(), &[]),
+            camera_buffer,
+            camera_bind_group,
+        }
+    }
+
+    /// Quads span `offset..offset + scale`; hexagons are centred on `offset`
+    /// with a radius of `scale`.
+    pub fn update(&mut self, gfx: &Graphics, quads: &[Instance], hexes: &[Instance]) {
+        gfx.context().queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraTransform::pixels(gfx.size())]),
+        );
+        self.quads.update(gfx.context(), quads);
+        self.hexes.update(gfx.context(), hexes);
+    }
+
+    /// Rebinds the camera, so world-space meshes must be drawn first.
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        self.quads.draw(gfx, render_pass);
+        self.hexes.draw(gfx, render_pass);
+    }
+}
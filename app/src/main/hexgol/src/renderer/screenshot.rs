@@ -0,0 +1,137 @@
+use super::*;
+use crate::export::{image_size, ExportOptions, Image};
+use crate::game::HexGOL;
+use crate::theme::Theme;
+
+/// Draws the board with the renderer into `gfx`, which should be headless: the
+/// grid, the live cells and the border, framed like `rasterize` frames them.
+/// `None` if the frame couldn't be read back.
+pub fn screenshot(
+    gfx: &mut Graphics,
+    game: &HexGOL,
+    theme: &Theme,
+    options: &ExportOptions,
+) -> Option<Image> {
+    let [width, height] = image_size(game.size(), options);
+    gfx.resize([width, height]);
+    gfx.set_clear_color(theme.background);
+    let camera = Camera::new([0.0, 0.0], 2.0 * options.cell_size / width as f32);
+    gfx.update(&camera);
+
+    let mut board = Board::new(gfx, game.size(), theme.live, theme.dying);
+    board.update(
+        gfx.context(),
+        game.iter()
+            .filter(|(_hex, cell)| **cell)
+            .map(|(hex, _cell)| [hex.q(), hex.r()]),
+    );
+    let grid = Grid::new(gfx, game.size(), theme.grid, theme.border);
+
+    let mut frame = gfx.start_frame()?;
+    let mut render_pass = frame.begin_pass(gfx);
+    if options.grid {
+        grid.draw_lines(gfx, &mut render_pass, camera.pixels_per_unit(gfx.size()));
+    }
+    board.draw(gfx, &mut render_pass);
+    grid.draw_border(gfx, &mut render_pass);
+    drop(render_pass);
+    gfx.end_frame(frame);
+
+    let pixels = gfx.read_pixels()?;
+    Some(Image {
+        width,
+        height,
+        pixels: pixels
+            .chunks(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::encode_png;
+    use crate::game::Pattern;
+
+    /// Pixels further than this from the stored picture in any channel differ.
+    const CHANNEL_TOLERANCE: u8 = 24;
+    /// Share of pixels that may differ, for drivers that place edges a little
+    /// differently.
+    const DIFFERING_TOLERANCE: f32 = 0.01;
+
+    fn golden_path(name: &str) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/renderer/golden")
+            .join(name)
+    }
+
+    fn decode(bytes: &[u8]) -> Image {
+        let mut reader = png::Decoder::new(bytes).read_info().expect("golden PNG");
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).expect("golden PNG");
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        Image {
+            width: info.width,
+            height: info.height,
+            pixels: buf[..info.buffer_size()]
+                .chunks(3)
+                .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                .collect(),
+        }
+    }
+
+    /// Compares a screenshot with the stored one, or stores it when
+    /// `HEXGOL_BLESS` is set.
+    fn check(name: &str, image: &Image) {
+        let path = golden_path(name);
+        if std::env::var_os("HEXGOL_BLESS").is_some() {
+            std::fs::write(&path, encode_png(image)).expect("golden written");
+            return;
+        }
+
+        let golden = decode(&std::fs::read(&path).expect("golden exists"));
+        assert_eq!([image.width, image.height], [golden.width, golden.height]);
+        let differing = image
+            .pixels
+            .iter()
+            .zip(&golden.pixels)
+            .filter(|(a, b)| (0..3).any(|c| a[c].abs_diff(b[c]) > CHANNEL_TOLERANCE))
+            .count();
+        assert!(
+            differing as f32 <= DIFFERING_TOLERANCE * image.pixels.len() as f32,
+            "{} of {} pixels differ from {}",
+            differing,
+            image.pixels.len(),
+            path.display()
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software EGL, see test_graphics"]
+    fn matches_the_golden_pictures() {
+        let mut gfx = test_graphics(4);
+
+        let pinwheel = Pattern::parse(include_str!("../../../assets/patterns/pinwheel.hexp"))
+            .expect("pattern parses");
+        let mut game = HexGOL::new(6);
+        for hex in &pinwheel.cells {
+            game.set(hex, true);
+        }
+
+        let options = ExportOptions::default();
+        for (name, theme) in [
+            ("classic.png", Theme::classic()),
+            ("light.png", Theme::light()),
+        ] {
+            let image = screenshot(&mut gfx, &game, &theme, &options).expect("screenshot");
+            check(name, &image);
+        }
+        let coarse = ExportOptions {
+            cell_size: 3.0,
+            grid: true,
+        };
+        let image = screenshot(&mut gfx, &game, &Theme::classic(), &coarse).expect("screenshot");
+        check("coarse.png", &image);
+    }
+}
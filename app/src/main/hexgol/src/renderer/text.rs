@@ -0,0 +1,270 @@
+use super::*;
+
+mod font {
+    include!(concat!(env!("OUT_DIR"), "/font.rs"));
+}
+
+/// A line of text in window pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub text: String,
+    /// Top left corner.
+    pub pos: [f32; 2],
+    /// Window pixels per font pixel.
+    pub scale: f32,
+    pub color: [f32; 3],
+}
+
+/// Size of a line of text in window pixels.
+pub fn text_size(text: &str, scale: f32) -> [f32; 2] {
+    let chars = text.chars().count() as f32;
+    [
+        // No gap after the last glyph.
+        (chars * font::CELL_SIZE[0] as f32 - 1.0).max(0.0) * scale,
+        font::GLYPH_SIZE[1] as f32 * scale,
+    ]
+}
+
+/// One character, the per-instance input of the text shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Glyph {
+    offset: [f32; 2],
+    scale: [f32; 2],
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+    color: [f32; 4],
+}
+impl Glyph {
+    const ATTRIBS: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32x2,
+        4 => Float32x2,
+        5 => Float32x4,
+    ];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Labels drawn in window pixels from the bitmap font baked by the build
+/// script. Each character is one instanced quad sampling the atlas.
+pub struct Text {
+    render_pipeline: wgpu::RenderPipeline,
+    quad: Mesh,
+    glyph_buffer: wgpu::Buffer,
+    capacity: BufferCapacity,
+    atlas_bind_group: wgpu::BindGroup,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+}
+impl Text {
+    pub fn new(gfx: &Graphics) -> Self {
+        let ctx = gfx.context();
+        let device = &ctx.device;
+
+        let atlas = device.create_texture_with_data(
+            &ctx.queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Font Atlas"),
+                size: wgpu::Extent3d {
+                    width: font::ATLAS_SIZE[0],
+                    height: font::ATLAS_SIZE[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            },
+            &font::ATLAS,
+        );
+        let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+        // Nearest filtering keeps the pixels crisp at integer scales.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Font Sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Font bind group"),
+        });
+        let atlas_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("font_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("../text.wgsl"));
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Text Pipeline Layout"),
+                bind_group_layouts: &[&gfx.camera_bind_group_layout, &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), Glyph::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gfx.format(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: gfx.sample_count(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let capacity = BufferCapacity::new(0);
+        let (camera_buffer, camera_bind_group) =
+            gfx.create_camera_binding(CameraTransform::pixels(gfx.size()));
+
+        Self {
+            render_pipeline,
+            quad: MeshBuilder::new_quad().build(ctx),
+            glyph_buffer: create_glyph_buffer(ctx, capacity.capacity()),
+            capacity,
+            atlas_bind_group,
+            camera_buffer,
+            camera_bind_group,
+        }
+    }
+
+    /// Lays the labels out into glyphs. Characters the font lacks show as `?`.
+    pub fn update(&mut self, gfx: &Graphics, labels: &[Label]) {
+        let ctx = gfx.context();
+        ctx.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraTransform::pixels(gfx.size())]),
+        );
+
+        let uv_scale = [
+            font::GLYPH_SIZE[0] as f32 / font::ATLAS_SIZE[0] as f32,
+            font::GLYPH_SIZE[1] as f32 / font::ATLAS_SIZE[1] as f32,
+        ];
+        let mut glyphs = vec![];
+        for label in labels {
+            let color = [label.color[0], label.color[1], label.color[2], 1.0];
+            let size = [
+                font::GLYPH_SIZE[0] as f32 * label.scale,
+                font::GLYPH_SIZE[1] as f32 * label.scale,
+            ];
+            let advance = font::CELL_SIZE[0] as f32 * label.scale;
+
+            for (i, c) in label.text.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                let index = (c as u32)
+                    .checked_sub(font::FIRST_CHAR as u32)
+                    .filter(|index| (*index as usize) < font::GLYPH_COUNT)
+                    .unwrap_or('?' as u32 - font::FIRST_CHAR as u32);
+                let cell = [
+                    index % font::ATLAS_COLUMNS * font::CELL_SIZE[0],
+                    index / font::ATLAS_COLUMNS * font::CELL_SIZE[1],
+                ];
+
+                glyphs.push(Glyph {
+                    offset: [label.pos[0] + i as f32 * advance, label.pos[1]],
+                    scale: size,
+                    uv_offset: [
+                        cell[0] as f32 / font::ATLAS_SIZE[0] as f32,
+                        cell[1] as f32 / font::ATLAS_SIZE[1] as f32,
+                    ],
+                    uv_scale,
+                    color,
+                });
+            }
+        }
+
+        if let Some(capacity) = self.capacity.resize(glyphs.len()) {
+            self.glyph_buffer = create_glyph_buffer(ctx, capacity);
+        }
+        ctx.queue
+            .write_buffer(&self.glyph_buffer, 0, bytemuck::cast_slice(&glyphs));
+    }
+
+    /// Rebinds the camera, so world-space meshes must be drawn first.
+    pub fn draw<'a>(&'a self, gfx: &'a Graphics, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.capacity.len() == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.atlas_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.quad.vb.slice(..));
+        render_pass.set_index_buffer(self.quad.ib.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_vertex_buffer(1, self.glyph_buffer.slice(..));
+        render_pass.draw_indexed(0..self.quad.indices, 0, 0..self.capacity.len() as u32);
+
+        gfx.bind_default(render_pass);
+    }
+}
+
+fn create_glyph_buffer(ctx: &WgpuContext, capacity: usize) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Glyph Buffer"),
+        size: (capacity * std::mem::size_of::<Glyph>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
@@ -0,0 +1,208 @@
+use crate::game::*;
+use crate::renderer::GraphicsBackend;
+use std::time::Duration;
+
+/// Rules offered in the settings panel, besides whatever rule is in use.
+pub const RULES: [Rule; 5] = [
+    Rule::new(1 << 2, 1 << 2),
+    Rule::new(1 << 2, 1 << 3 | 1 << 4),
+    Rule::new(1 << 2, 1 << 2 | 1 << 3),
+    Rule::new(1 << 3, 1 << 2 | 1 << 3),
+    Rule::new(1 << 2 | 1 << 4, 1 << 3 | 1 << 5),
+];
+/// Generations per second to pick from.
+pub const SPEEDS: [u32; 7] = [1, 2, 5, 10, 15, 30, 60];
+/// Board radii to pick from.
+pub const BOARD_SIZES: [i32; 6] = [15, 25, 35, 50, 75, 100];
+/// Undo history sizes to pick from, in MiB.
+pub const HISTORY_SIZES: [usize; 5] = [4, 16, 64, 128, 256];
+
+/// What the player chose in the settings panel, kept between runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub rule: Rule,
+    pub neighborhood: Neighborhood,
+    pub topology: Topology,
+    /// Generations per second.
+    pub speed: u32,
+    /// Name of the theme. Without one, the theme config is used if there is
+    /// one.
+    pub theme: Option<String>,
+    /// Radius of the board in cells. Only read at startup.
+    pub board_size: i32,
+    /// Memory the undo history may take, in MiB.
+    pub history: usize,
+    /// Graphics API to draw with. Only read at startup.
+    pub backend: GraphicsBackend,
+}
+impl Settings {
+    /// Simulation step length.
+    pub fn tick(&self) -> Duration {
+        Duration::from_nanos(1_000_000_000 / self.speed.max(1) as u64)
+    }
+    /// Bytes the undo history may take.
+    pub fn history_budget(&self) -> usize {
+        self.history * 1024 * 1024
+    }
+
+    /// Reads `key = value` lines, as written by `to_text`. Keys left out keep
+    /// their defaults, and keys this version doesn't know are skipped. `#`
+    /// starts a comment when it begins a line.
+    pub fn parse(text: &str) -> Result<Self, SettingsError> {
+        let mut settings = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(SettingsError::Malformed(i + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value = SettingsError::BadValue(i + 1);
+
+            match key {
+                "rule" => settings.rule = value.parse().map_err(|_| bad_value)?,
+                "neighborhood" => {
+                    settings.neighborhood = Neighborhood::from_name(value).ok_or(bad_value)?
+                }
+                "topology" => settings.topology = Topology::from_name(value).ok_or(bad_value)?,
+                "speed" => {
+                    settings.speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed| *speed > 0)
+                        .ok_or(bad_value)?
+                }
+                "theme" => settings.theme = Some(value.to_string()),
+                "board_size" => {
+                    settings.board_size = value
+                        .parse()
+                        .ok()
+                        .filter(|size| BOARD_SIZES.contains(size))
+                        .ok_or(bad_value)?
+                }
+                "history" => {
+                    settings.history = value
+                        .parse()
+                        .ok()
+                        .filter(|size| HISTORY_SIZES.contains(size))
+                        .ok_or(bad_value)?
+                }
+                "backend" => {
+                    settings.backend = GraphicsBackend::from_name(value).ok_or(bad_value)?
+                }
+                _ => log::warn!("Skipping unknown setting `{}` on line {}", key, i + 1),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "rule = {}\nneighborhood = {}\ntopology = {}\nspeed = {}\n",
+            self.rule,
+            self.neighborhood.name(),
+            self.topology.name(),
+            self.speed
+        );
+        if let Some(theme) = &self.theme {
+            text += &format!("theme = {}\n", theme);
+        }
+        text += &format!("board_size = {}\n", self.board_size);
+        text += &format!("history = {}\n", self.history);
+        text += &format!("backend = {}\n", self.backend.name());
+        text
+    }
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            rule: Rule::default(),
+            neighborhood: Neighborhood::default(),
+            topology: Topology::default(),
+            speed: 15,
+            theme: None,
+            board_size: 35,
+            history: 16,
+            backend: GraphicsBackend::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SettingsError {
+    /// The line isn't `key = value`.
+    Malformed(usize),
+    BadValue(usize),
+}
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(line) => write!(f, "line {}: expected `key = value`", line),
+            Self::BadValue(line) => write!(f, "line {}: bad value", line),
+        }
+    }
+}
+
+/// The option `steps` away from `current` in `options`, wrapping around. A
+/// value not among the options steps from the first one.
+pub fn step_option<T: PartialEq + Clone>(options: &[T], current: &T, steps: isize) -> T {
+    let len = options.len() as isize;
+    let i = options.iter().position(|o| o == current).unwrap_or(0) as isize;
+    options[(i + steps).rem_euclid(len) as usize].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_text() {
+        let defaults = Settings::default();
+        assert_eq!(Settings::parse(&defaults.to_text()), Ok(defaults));
+
+        let chosen = Settings {
+            rule: RULES[4],
+            neighborhood: Neighborhood::Extended,
+            topology: Topology::Wrapped,
+            speed: 60,
+            theme: Some("Night".to_string()),
+            board_size: 100,
+            history: 256,
+            backend: GraphicsBackend::Gles,
+        };
+        assert_eq!(Settings::parse(&chosen.to_text()), Ok(chosen));
+    }
+
+    #[test]
+    fn skips_unknown_keys() {
+        let settings = Settings::parse("# comment\nspeed = 5\nzoom = 2\n").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                speed: 5,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert_eq!(Settings::parse("speed"), Err(SettingsError::Malformed(1)));
+        assert_eq!(
+            Settings::parse("\nspeed = 0"),
+            Err(SettingsError::BadValue(2))
+        );
+        assert_eq!(
+            Settings::parse("board_size = 40"),
+            Err(SettingsError::BadValue(1))
+        );
+        assert_eq!(
+            Settings::parse("backend = metal"),
+            Err(SettingsError::BadValue(1))
+        );
+    }
+}
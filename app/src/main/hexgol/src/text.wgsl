@@ -0,0 +1,50 @@
+struct CameraTransform {
+    scale: vec2<f32>,
+    offset: vec2<f32>,
+}
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+};
+
+struct Glyph {
+    // Top left corner and size of the quad.
+    @location(1) offset: vec2<f32>,
+    @location(2) scale: vec2<f32>,
+    // The glyph's rectangle in the atlas, in texture coordinates.
+    @location(3) uv_offset: vec2<f32>,
+    @location(4) uv_scale: vec2<f32>,
+    @location(5) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraTransform;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+    glyph: Glyph,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = in.position * glyph.uv_scale + glyph.uv_offset;
+    out.color = glyph.color;
+    out.clip_position = vec4<f32>((in.position * glyph.scale + glyph.offset - camera.offset) * camera.scale, 1.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas, atlas_sampler, in.uv).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
@@ -0,0 +1,251 @@
+/// Colours the board is drawn with. Components are linear, as the shaders
+/// output them; config files give sRGB hex codes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub background: [f32; 3],
+    pub live: [f32; 3],
+    /// Cells that died in the last generation.
+    pub dying: [f32; 3],
+    pub grid: [f32; 3],
+    pub border: [f32; 3],
+    pub selection: [f32; 3],
+    /// Patterns waiting to be placed.
+    pub preview: [f32; 3],
+}
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            background: [0.01, 0.01, 0.01],
+            live: [1.0, 1.0, 1.0],
+            dying: [0.35, 0.1, 0.1],
+            grid: [0.2, 0.2, 0.2],
+            border: [0.9, 0.6, 0.2],
+            selection: [1.0, 0.8, 0.2],
+            preview: [0.3, 0.6, 1.0],
+        }
+    }
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            background: srgb(0xf4f1ea),
+            live: srgb(0x202124),
+            dying: srgb(0xc9a9a6),
+            grid: srgb(0xd8d4cc),
+            border: srgb(0x8a5a00),
+            selection: srgb(0xf2b134),
+            preview: srgb(0x3b78c3),
+        }
+    }
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_string(),
+            background: srgb(0x000000),
+            live: srgb(0xffffff),
+            dying: srgb(0x808080),
+            grid: srgb(0x606060),
+            border: srgb(0xffff00),
+            selection: srgb(0x00ffff),
+            preview: srgb(0xff00ff),
+        }
+    }
+    /// Okabe-Ito colours, which stay distinct under the common colour vision
+    /// deficiencies.
+    pub fn colour_blind() -> Self {
+        Self {
+            name: "colour-blind".to_string(),
+            background: srgb(0x101010),
+            live: srgb(0x56b4e9),
+            dying: srgb(0xd55e00),
+            grid: srgb(0x3a3a3a),
+            border: srgb(0xf0e442),
+            selection: srgb(0xe69f00),
+            preview: srgb(0x009e73),
+        }
+    }
+
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::classic(),
+            Self::light(),
+            Self::high_contrast(),
+            Self::colour_blind(),
+        ]
+    }
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::builtin().into_iter().find(|theme| theme.name == name)
+    }
+
+    /// Reads a theme from `key = value` lines. `base` names a built-in theme to
+    /// start from, which defaults to classic, and applies first wherever it's
+    /// written; the other keys are colours given as `#rrggbb`. Keys this
+    /// version doesn't know are skipped. `#` starts a comment when it begins a
+    /// line.
+    pub fn parse(text: &str) -> Result<Self, ThemeError> {
+        let mut lines = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or(ThemeError::Malformed(i + 1))?;
+            lines.push((i + 1, key.trim(), value.trim()));
+        }
+
+        let mut base = None;
+        for (line, key, value) in &lines {
+            if *key == "base" {
+                if base.is_some() {
+                    return Err(ThemeError::DuplicateBase(*line));
+                }
+                base = Some(Self::by_name(value).ok_or(ThemeError::UnknownBase(*line))?);
+            }
+        }
+        let mut theme = base.unwrap_or_else(Self::classic);
+        theme.name = "custom".to_string();
+
+        for (line, key, value) in lines {
+            let color = match key {
+                "base" => continue,
+                "name" => {
+                    theme.name = value.to_string();
+                    continue;
+                }
+                "background" => &mut theme.background,
+                "live" => &mut theme.live,
+                "dying" => &mut theme.dying,
+                "grid" => &mut theme.grid,
+                "border" => &mut theme.border,
+                "selection" => &mut theme.selection,
+                "preview" => &mut theme.preview,
+                _ => {
+                    log::warn!("Skipping unknown theme key `{}` on line {}", key, line);
+                    continue;
+                }
+            };
+            *color = parse_color(value).ok_or(ThemeError::BadColor(line))?;
+        }
+
+        Ok(theme)
+    }
+}
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ThemeError {
+    /// The line isn't `key = value`.
+    Malformed(usize),
+    UnknownBase(usize),
+    /// A second `base`.
+    DuplicateBase(usize),
+    BadColor(usize),
+}
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(line) => write!(f, "line {}: expected `key = value`", line),
+            Self::UnknownBase(line) => write!(f, "line {}: no built-in theme by that name", line),
+            Self::DuplicateBase(line) => write!(f, "line {}: only one base is allowed", line),
+            Self::BadColor(line) => write!(f, "line {}: colours look like #rrggbb", line),
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Option<[f32; 3]> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(srgb)
+}
+
+/// Converts a `0xrrggbb` sRGB colour to linear components.
+pub fn srgb(rgb: u32) -> [f32; 3] {
+    let channel = |shift: u32| {
+        let c = ((rgb >> shift) & 0xff) as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [channel(16), channel(8), channel(0)]
+}
+
+/// Converts linear components to 8-bit sRGB, the inverse of `srgb`.
+pub fn to_srgb8(color: [f32; 3]) -> [u8; 3] {
+    color.map(|c| {
+        let c = c.clamp(0.0, 1.0);
+        let c = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c * 255.0).round() as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_colours_over_the_classic_theme() {
+        let theme = Theme::parse("# Mine\nname = mine\n\nlive = #ff0000\ngrid=#000000\n")
+            .expect("theme parses");
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.live, [1.0, 0.0, 0.0]);
+        assert_eq!(theme.grid, [0.0, 0.0, 0.0]);
+        assert_eq!(theme.border, Theme::classic().border);
+    }
+
+    #[test]
+    fn applies_the_base_first() {
+        let before = Theme::parse("live = #ff0000\nbase = light\n").expect("theme parses");
+        let after = Theme::parse("base = light\nlive = #ff0000\n").expect("theme parses");
+        assert_eq!(before, after);
+        assert_eq!(before.live, [1.0, 0.0, 0.0]);
+        assert_eq!(before.background, Theme::light().background);
+        assert_eq!(before.name, "custom");
+    }
+
+    #[test]
+    fn skips_unknown_keys() {
+        let theme = Theme::parse("glow = #ffffff\nlive = #ff0000\n").expect("theme parses");
+        assert_eq!(theme.live, [1.0, 0.0, 0.0]);
+        // The key is checked before its value.
+        let theme = Theme::parse("shadow = soft\n").expect("theme parses");
+        assert_eq!(
+            theme,
+            Theme {
+                name: "custom".to_string(),
+                ..Theme::classic()
+            }
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(Theme::parse("live #ffffff"), Err(ThemeError::Malformed(1)));
+        assert_eq!(Theme::parse("\nlive = white"), Err(ThemeError::BadColor(2)));
+        assert_eq!(Theme::parse("live = #fff"), Err(ThemeError::BadColor(1)));
+        assert_eq!(Theme::parse("base = neon"), Err(ThemeError::UnknownBase(1)));
+        assert_eq!(
+            Theme::parse("base = light\nbase = classic"),
+            Err(ThemeError::DuplicateBase(2))
+        );
+    }
+
+    #[test]
+    fn converts_to_and_from_srgb() {
+        for rgb in [0x000000, 0xffffff, 0x3b78c3, 0x0a0b0c] {
+            let [r, g, b] = to_srgb8(srgb(rgb));
+            assert_eq!(u32::from_be_bytes([0, r, g, b]), rgb);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 package kotexander.hexgol
 
 //import android.view.View
+import android.app.AlertDialog
+import android.content.Intent
+import android.util.Log
+import android.view.WindowInsets
+import androidx.core.content.FileProvider
 import com.google.androidgamesdk.GameActivity
+import java.io.File
+import java.io.IOException
 
 class MainActivity : GameActivity() {
     companion object {
@@ -10,6 +17,73 @@ class MainActivity : GameActivity() {
         }
     }
 
+    /** Left, top, right and bottom pixels covered by system bars and cutouts. Called from native code. */
+    @Suppress("unused")
+    fun windowInsets(): IntArray {
+        val insets = window.decorView.rootWindowInsets
+            ?.getInsets(WindowInsets.Type.systemBars() or WindowInsets.Type.displayCutout())
+            ?: return IntArray(4)
+        return intArrayOf(insets.left, insets.top, insets.right, insets.bottom)
+    }
+
+    /** Explains a failure the game can't recover from, then closes. Called from native code. */
+    @Suppress("unused")
+    fun showError(message: String) {
+        runOnUiThread {
+            AlertDialog.Builder(this)
+                .setTitle(R.string.error_title)
+                .setMessage(message)
+                .setCancelable(false)
+                .setPositiveButton(android.R.string.ok) { _, _ -> finish() }
+                .show()
+        }
+    }
+
+    /** Offers a file from the export directory to other apps. Called from native code. */
+    @Suppress("unused")
+    fun shareFile(path: String, mimeType: String) {
+        val uri = FileProvider.getUriForFile(this, "$packageName.exports", File(path))
+        val intent = Intent(Intent.ACTION_SEND)
+            .setType(mimeType)
+            .putExtra(Intent.EXTRA_STREAM, uri)
+            .addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+        runOnUiThread { startActivity(Intent.createChooser(intent, null)) }
+    }
+
+    /** Offers text, like a pattern, to other apps. Called from native code. */
+    @Suppress("unused")
+    fun shareText(text: String) {
+        val intent = Intent(Intent.ACTION_SEND)
+            .setType("text/plain")
+            .putExtra(Intent.EXTRA_TEXT, text)
+        runOnUiThread { startActivity(Intent.createChooser(intent, null)) }
+    }
+
+    /**
+     * The text of a file another app opened the game with, or null. Each file is only
+     * returned once. Called from native code.
+     */
+    @Suppress("unused")
+    fun takeOpenedFile(): String? {
+        val uri = intent?.takeIf { it.action == Intent.ACTION_VIEW }?.data ?: return null
+        intent = Intent(this, MainActivity::class.java)
+        return try {
+            contentResolver.openInputStream(uri)?.bufferedReader()?.use { it.readText() }
+        } catch (e: IOException) {
+            Log.w("HexGoL", "Couldn't read $uri", e)
+            null
+        } catch (e: SecurityException) {
+            Log.w("HexGoL", "Not allowed to read $uri", e)
+            null
+        }
+    }
+
+    // The game runs as a single task, so files opened while it's running arrive here.
+    override fun onNewIntent(intent: Intent) {
+        super.onNewIntent(intent)
+        setIntent(intent)
+    }
+
 //    override fun onWindowFocusChanged(hasFocus: Boolean) {
 //        super.onWindowFocusChanged(hasFocus)
 //        if (hasFocus) {